@@ -0,0 +1,2 @@
+ALTER TABLE words
+    DROP COLUMN match_inside_words;
@@ -0,0 +1,2 @@
+ALTER TABLE words
+    ADD COLUMN match_inside_words boolean NOT NULL DEFAULT false;
@@ -0,0 +1,5 @@
+CREATE TABLE guild_settings
+(
+    guild_id            bigint  NOT NULL PRIMARY KEY,
+    spoiler_attachments boolean NOT NULL DEFAULT false
+);
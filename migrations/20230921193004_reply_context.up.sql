@@ -0,0 +1,2 @@
+ALTER TABLE guild_settings
+    ADD COLUMN reply_context boolean NOT NULL DEFAULT true;
@@ -0,0 +1,2 @@
+ALTER TABLE guild_settings
+    ADD COLUMN spoiler_edits boolean NOT NULL DEFAULT false;
@@ -0,0 +1,15 @@
+DELETE
+FROM allowed_words allowed
+WHERE EXISTS (
+    SELECT
+        1
+    FROM
+        allowed_words other
+    WHERE
+        other.guild_id = allowed.guild_id
+        AND other.word_id < allowed.word_id);
+
+ALTER TABLE allowed_words
+    DROP CONSTRAINT allowed_words_word_id_fkey,
+    DROP CONSTRAINT allowed_words_pkey,
+    ADD PRIMARY KEY (guild_id);
@@ -0,0 +1,14 @@
+DELETE
+FROM allowed_words
+WHERE NOT EXISTS (
+    SELECT
+        1
+    FROM
+        words
+    WHERE
+        id = word_id);
+
+ALTER TABLE allowed_words
+    DROP CONSTRAINT allowed_words_pkey,
+    ADD PRIMARY KEY (guild_id, word_id),
+    ADD FOREIGN KEY (word_id) REFERENCES words (id) ON DELETE CASCADE;
@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings
+    DROP COLUMN normalize,
+    DROP COLUMN leet_map;
@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings
+    ADD COLUMN normalize boolean NOT NULL DEFAULT false,
+    ADD COLUMN leet_map text NOT NULL DEFAULT '4a 3e 1i 0o 5s 7t @a $s';
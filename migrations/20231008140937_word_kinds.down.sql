@@ -0,0 +1,11 @@
+ALTER TABLE words
+    ADD COLUMN match_inside_words boolean NOT NULL DEFAULT false;
+
+UPDATE words
+SET match_inside_words = kind IN ('prefix', 'suffix', 'substring');
+
+ALTER TABLE words
+    DROP COLUMN kind,
+    DROP COLUMN inflections;
+
+DROP TYPE word_kind;
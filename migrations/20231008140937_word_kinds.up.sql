@@ -0,0 +1,16 @@
+CREATE TYPE word_kind AS ENUM ('word', 'phrase', 'prefix', 'suffix', 'substring');
+
+ALTER TABLE words
+    ADD COLUMN kind word_kind NOT NULL DEFAULT 'word',
+    ADD COLUMN inflections boolean NOT NULL DEFAULT false;
+
+UPDATE words
+SET kind = 'phrase'
+WHERE word ~ '\s';
+
+UPDATE words
+SET kind = 'substring'
+WHERE match_inside_words;
+
+ALTER TABLE words
+    DROP COLUMN match_inside_words;
@@ -0,0 +1,14 @@
+DELETE
+FROM words
+WHERE kind = 'regex';
+
+ALTER TYPE word_kind RENAME TO word_kind_old;
+
+CREATE TYPE word_kind AS ENUM ('word', 'phrase', 'prefix', 'suffix', 'substring');
+
+ALTER TABLE words
+    ALTER COLUMN kind DROP DEFAULT,
+    ALTER COLUMN kind TYPE word_kind USING kind::text::word_kind,
+    ALTER COLUMN kind SET DEFAULT 'word';
+
+DROP TYPE word_kind_old;
@@ -0,0 +1 @@
+ALTER TYPE word_kind ADD VALUE 'regex';
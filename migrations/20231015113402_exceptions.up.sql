@@ -0,0 +1,6 @@
+CREATE TABLE exceptions
+(
+    guild_id  bigint NOT NULL,
+    exception text   NOT NULL,
+    PRIMARY KEY (guild_id, exception)
+);
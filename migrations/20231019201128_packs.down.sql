@@ -0,0 +1,6 @@
+ALTER TABLE words
+    DROP COLUMN pack_id;
+
+DROP TABLE guild_packs;
+
+DROP TABLE packs;
@@ -0,0 +1,34 @@
+CREATE TABLE packs
+(
+    id                 serial PRIMARY KEY,
+    name               text    NOT NULL UNIQUE,
+    description        text    NOT NULL DEFAULT '',
+    enabled_by_default boolean NOT NULL DEFAULT true
+);
+
+CREATE TABLE guild_packs
+(
+    guild_id bigint  NOT NULL,
+    pack_id  int     NOT NULL REFERENCES packs (id) ON DELETE CASCADE,
+    enabled  boolean NOT NULL,
+    PRIMARY KEY (guild_id, pack_id)
+);
+
+INSERT INTO packs (name, description)
+VALUES ('general', 'the words that were put in spoilers before packs were added');
+
+ALTER TABLE words
+    ADD COLUMN pack_id int REFERENCES packs (id) ON DELETE CASCADE;
+
+UPDATE words
+SET pack_id = (
+    SELECT
+        id
+    FROM
+        packs
+    WHERE
+        name = 'general')
+WHERE guild_id IS NULL;
+
+ALTER TABLE words
+    ADD CHECK ((guild_id IS NULL) = (pack_id IS NOT NULL));
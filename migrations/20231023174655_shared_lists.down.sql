@@ -0,0 +1,3 @@
+DROP TABLE subscriptions;
+
+DROP TABLE shared_lists;
@@ -0,0 +1,12 @@
+CREATE TABLE shared_lists
+(
+    guild_id bigint NOT NULL PRIMARY KEY,
+    code     text   NOT NULL UNIQUE
+);
+
+CREATE TABLE subscriptions
+(
+    guild_id     bigint NOT NULL,
+    publisher_id bigint NOT NULL REFERENCES shared_lists (guild_id) ON DELETE CASCADE,
+    PRIMARY KEY (guild_id, publisher_id)
+);
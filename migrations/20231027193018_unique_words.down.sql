@@ -0,0 +1 @@
+DROP INDEX words_guild_id_word_kind_index;
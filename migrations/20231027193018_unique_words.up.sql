@@ -0,0 +1,15 @@
+UPDATE words
+SET word = regexp_replace(trim(word), '\s+', ' ', 'g')
+WHERE kind <> 'regex';
+
+DELETE
+FROM words
+WHERE id NOT IN (
+    SELECT
+        min(id)
+    FROM
+        words
+    GROUP BY
+        COALESCE(guild_id, 0), word, kind);
+
+CREATE UNIQUE INDEX words_guild_id_word_kind_index ON words (COALESCE(guild_id, 0), word, kind);
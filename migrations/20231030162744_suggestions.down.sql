@@ -0,0 +1,5 @@
+DROP TABLE suggesting_guilds;
+
+DROP TABLE suggestions;
+
+DROP TYPE suggestion_status;
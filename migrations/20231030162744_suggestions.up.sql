@@ -0,0 +1,19 @@
+CREATE TYPE suggestion_status AS ENUM ('pending', 'approved', 'rejected');
+
+CREATE TABLE suggestions
+(
+    id          serial PRIMARY KEY,
+    word        text              NOT NULL,
+    kind        word_kind         NOT NULL,
+    inflections boolean           NOT NULL DEFAULT false,
+    status      suggestion_status NOT NULL DEFAULT 'pending',
+    UNIQUE (word, kind)
+);
+
+CREATE TABLE suggesting_guilds
+(
+    suggestion_id int    NOT NULL REFERENCES suggestions (id) ON DELETE CASCADE,
+    guild_id      bigint NOT NULL,
+    channel_id    bigint NOT NULL,
+    PRIMARY KEY (suggestion_id, guild_id)
+);
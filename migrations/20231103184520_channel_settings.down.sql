@@ -0,0 +1,14 @@
+DROP INDEX words_guild_id_channel_id_word_kind_index;
+
+DELETE
+FROM words
+WHERE channel_id IS NOT NULL;
+
+ALTER TABLE words
+    DROP COLUMN channel_id;
+
+CREATE UNIQUE INDEX words_guild_id_word_kind_index ON words (COALESCE(guild_id, 0), word, kind);
+
+DROP TABLE channel_packs;
+
+DROP TABLE channel_settings;
@@ -0,0 +1,22 @@
+CREATE TABLE channel_settings
+(
+    channel_id bigint  NOT NULL PRIMARY KEY,
+    guild_id   bigint  NOT NULL,
+    enabled    boolean NOT NULL DEFAULT true
+);
+
+CREATE TABLE channel_packs
+(
+    channel_id bigint NOT NULL REFERENCES channel_settings (channel_id) ON DELETE CASCADE,
+    pack_id    int    NOT NULL REFERENCES packs (id) ON DELETE CASCADE,
+    PRIMARY KEY (channel_id, pack_id)
+);
+
+ALTER TABLE words
+    ADD COLUMN channel_id bigint REFERENCES channel_settings (channel_id) ON DELETE CASCADE,
+    ADD CHECK (channel_id IS NULL OR guild_id IS NOT NULL);
+
+DROP INDEX words_guild_id_word_kind_index;
+
+CREATE UNIQUE INDEX words_guild_id_channel_id_word_kind_index
+    ON words (COALESCE(guild_id, 0), COALESCE(channel_id, 0), word, kind);
@@ -0,0 +1,3 @@
+DROP TABLE opted_out_users;
+
+DROP TABLE bypass_roles;
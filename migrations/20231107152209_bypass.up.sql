@@ -0,0 +1,13 @@
+CREATE TABLE bypass_roles
+(
+    guild_id bigint NOT NULL,
+    role_id  bigint NOT NULL,
+    PRIMARY KEY (guild_id, role_id)
+);
+
+CREATE TABLE opted_out_users
+(
+    guild_id bigint NOT NULL,
+    user_id  bigint NOT NULL,
+    PRIMARY KEY (guild_id, user_id)
+);
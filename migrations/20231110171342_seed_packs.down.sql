@@ -0,0 +1,19 @@
+UPDATE words
+SET pack_id = (
+    SELECT
+        id
+    FROM
+        packs
+    WHERE
+        name = 'general')
+WHERE pack_id IN (
+    SELECT
+        id
+    FROM
+        packs
+    WHERE
+        name IN ('profanity', 'self-harm', 'eating disorders', 'violence', 'phobias'));
+
+DELETE
+FROM packs
+WHERE name IN ('profanity', 'self-harm', 'eating disorders', 'violence', 'phobias');
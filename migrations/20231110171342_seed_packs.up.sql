@@ -0,0 +1,8 @@
+INSERT INTO packs (name, description)
+VALUES ('profanity', 'swear words and slurs'),
+       ('self-harm', 'self-harm and suicide'),
+       ('eating disorders', 'eating disorders, dieting and weight'),
+       ('violence', 'violence, gore and abuse'),
+       ('phobias', 'common phobias, like spiders, needles or vomit')
+ON CONFLICT (name)
+    DO NOTHING;
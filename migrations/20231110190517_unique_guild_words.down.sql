@@ -0,0 +1,4 @@
+DROP INDEX words_guild_id_channel_id_word_index;
+
+CREATE UNIQUE INDEX words_guild_id_channel_id_word_kind_index
+    ON words (COALESCE(guild_id, 0), COALESCE(channel_id, 0), word, kind);
@@ -0,0 +1,16 @@
+-- channel_id is part of the key since a word can be added to the guild and to
+-- each of its channels separately
+DELETE
+FROM words
+WHERE id NOT IN (
+    SELECT
+        min(id)
+    FROM
+        words
+    GROUP BY
+        COALESCE(guild_id, 0), COALESCE(channel_id, 0), word);
+
+DROP INDEX words_guild_id_channel_id_word_kind_index;
+
+CREATE UNIQUE INDEX words_guild_id_channel_id_word_index
+    ON words (COALESCE(guild_id, 0), COALESCE(channel_id, 0), word);
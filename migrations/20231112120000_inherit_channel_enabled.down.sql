@@ -0,0 +1,7 @@
+UPDATE channel_settings
+SET enabled = true
+WHERE enabled IS NULL;
+
+ALTER TABLE channel_settings
+    ALTER COLUMN enabled SET DEFAULT true,
+    ALTER COLUMN enabled SET NOT NULL;
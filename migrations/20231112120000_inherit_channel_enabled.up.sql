@@ -0,0 +1,3 @@
+ALTER TABLE channel_settings
+    ALTER COLUMN enabled DROP NOT NULL,
+    ALTER COLUMN enabled DROP DEFAULT;
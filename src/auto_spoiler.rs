@@ -1,26 +1,71 @@
+use std::{ops::Range, sync::Arc};
+
 use anyhow::{Context as _, Result};
+use futures_util::future;
 use twilight_model::{
-    application::component::{button::ButtonStyle, ActionRow, Button, Component},
-    channel::Message,
-    guild::Permissions,
+    application::component::{select_menu::SelectMenuOption, ActionRow, Component, SelectMenu},
+    channel::{message::MessageType, Message},
+    gateway::payload::incoming::MessageUpdate,
+    guild::{PartialMember, Permissions, PremiumTier},
+    http::attachment::Attachment,
+    id::{
+        marker::{GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+};
+
+use crate::{
+    bypass, channel_pair, database, download,
+    filter::{self, Filter},
+    webhook, Context,
 };
 
-use crate::{channel_pair, database, webhook, Context};
+mod markdown;
+
+/// the custom id of the select menu to allow the words in a message
+pub const ALLOW_CUSTOM_ID: &str = "allow";
+
+/// the maximum number of characters in a message
+const MAX_CONTENT_LEN: usize = 2000;
+
+/// a message's content with the words in it put in spoilers
+pub struct Spoilered {
+    pub content: String,
+    /// the select menu to allow the words that were put in spoilers
+    pub components: Vec<Component>,
+}
 
+/// puts the words in the message in spoilers, skipping it if it's already being
+/// put in spoilers
 pub async fn edit(ctx: Context, message: Message) -> Result<()> {
-    if message.author.bot {
+    if message.author.bot || !ctx.editing.insert(message.id) {
         return Ok(());
     }
 
+    let message_id = message.id;
+    let result = spoiler_message(Arc::clone(&ctx), message).await;
+    ctx.editing.remove(&message_id);
+
+    result
+}
+
+async fn spoiler_message(ctx: Context, message: Message) -> Result<()> {
     let guild_id = message
         .guild_id
         .context("message doesn't have a guild id")?;
-    let mut content = message.content.to_lowercase();
-    let mut filter_words = database::words(&ctx.db, guild_id).await?;
-    filter_words.retain(|word| content.contains(&word.word));
-    if filter_words.is_empty() {
+    let roles = message
+        .member
+        .as_ref()
+        .map_or(&[][..], |member| &member.roles);
+    if bypasses(&ctx, guild_id, message.author.id, roles).await? {
         return Ok(());
     }
+    let Some(filter) = filter::get_in_channel(&ctx, guild_id, message.channel_id).await? else {
+        return Ok(());
+    };
+    let Some(mut spoilered) = spoiler_words(&filter, &message.content) else {
+        return Ok(());
+    };
 
     let permissions = ctx
         .cache
@@ -39,30 +84,36 @@ pub async fn edit(ctx: Context, message: Message) -> Result<()> {
         }
         return Ok(());
     }
-    let components = if filter_words.len() == 1 {
-        vec![Component::ActionRow(ActionRow {
-            components: vec![Component::Button(Button {
-                custom_id: Some(
-                    filter_words
-                        .first()
-                        .context("filter words is empty")?
-                        .id
-                        .to_string(),
-                ),
-                label: Some("allow this word (moderator only)".to_owned()),
-                style: ButtonStyle::Danger,
-                disabled: false,
-                emoji: None,
-                url: None,
-            })],
-        })]
-    } else {
-        vec![]
-    };
+    let settings = database::settings(&ctx.db, guild_id).await?;
 
-    for word in filter_words {
-        content = content.replace(&word.word, &format!("||{}||", word.word));
+    if settings.reply_context {
+        // the header is left out if the message would be too long with it
+        if let Some(header) = reply_header(&message, guild_id).filter(|header| {
+            header
+                .chars()
+                .count()
+                .saturating_add(spoilered.content.chars().count())
+                <= MAX_CONTENT_LEN
+        }) {
+            spoilered.content.insert_str(0, &header);
+        }
     }
+    let Some(attachments) =
+        attachments(&ctx, &message, guild_id, settings.spoiler_attachments).await?
+    else {
+        if permissions.contains(Permissions::SEND_MESSAGES) {
+            ctx.http
+                .create_message(message.channel_id)
+                .reply(message.id)
+                .content(
+                    "there's a word to put in spoilers here but the files are too large for me \
+                     to send again, please send it again with the word in spoilers",
+                )?
+                .exec()
+                .await?;
+        }
+        return Ok(());
+    };
 
     let channel = ctx
         .cache
@@ -79,8 +130,9 @@ pub async fn edit(ctx: Context, message: Message) -> Result<()> {
             .context("message doesn't have a guild id")?,
         channel_id,
         thread_id,
-        &content,
-        &components,
+        &spoilered.content,
+        &spoilered.components,
+        &attachments,
     )
     .await?;
 
@@ -91,3 +143,313 @@ pub async fn edit(ctx: Context, message: Message) -> Result<()> {
 
     Ok(())
 }
+
+/// puts the words of the filter in `content` in spoilers, returns `None` if
+/// there are no words to put in spoilers
+pub fn spoiler_words(filter: &Filter, content: &str) -> Option<Spoilered> {
+    let protected = markdown::protected(content);
+    let mut matches = filter.find(content);
+    matches.retain(|found| !markdown::is_protected(&protected, &found.span));
+    if matches.is_empty() {
+        return None;
+    }
+
+    let mut words: Vec<_> = matches.iter().map(|found| found.word).collect();
+    words.sort_unstable_by_key(|word| word.id);
+    words.dedup_by_key(|word| word.id);
+    let options: Vec<_> = words
+        .into_iter()
+        .take(25)
+        .map(|word| SelectMenuOption {
+            default: false,
+            description: None,
+            emoji: None,
+            label: word.to_string().chars().take(100).collect(),
+            value: word.id.to_string(),
+        })
+        .collect();
+
+    let spans = merge(matches.into_iter().map(|found| found.span).collect());
+
+    Some(Spoilered {
+        content: spoiler(content, &spans),
+        components: vec![Component::ActionRow(ActionRow {
+            components: vec![Component::SelectMenu(SelectMenu {
+                custom_id: ALLOW_CUSTOM_ID.to_owned(),
+                disabled: false,
+                max_values: options.len().try_into().ok(),
+                min_values: Some(1),
+                placeholder: Some("allow words (moderator only)".to_owned()),
+                options,
+            })],
+        })],
+    })
+}
+
+/// removes the spoilers around the words of the filter in `content`, keeping
+/// the spoilers the author wrote
+///
+/// a spoiler is removed only if the words of the filter cover all of it, like
+/// the spoilers added by [`spoiler_words`]
+#[allow(
+    clippy::integer_arithmetic,
+    clippy::string_slice,
+    clippy::indexing_slicing
+)]
+pub fn unspoiler_words(filter: &Filter, content: &str) -> String {
+    let mut unspoilered = String::with_capacity(content.len());
+    let mut last = 0;
+
+    for span in markdown::protected(content) {
+        let text = &content[span.clone()];
+        let Some(inner) = text
+            .strip_prefix("||")
+            .and_then(|rest| rest.strip_suffix("||"))
+        else {
+            continue;
+        };
+        let covered = merge(
+            filter
+                .find(inner)
+                .into_iter()
+                .map(|found| found.span)
+                .collect(),
+        );
+        if !matches!(covered.as_slice(), [range] if range.start == 0 && range.end == inner.len()) {
+            continue;
+        }
+
+        unspoilered.push_str(&content[last..span.start]);
+        unspoilered.push_str(inner);
+        last = span.end;
+    }
+    unspoilered.push_str(&content[last..]);
+
+    unspoilered
+}
+
+/// runs [`edit`] on an edited message if the guild puts words edited into
+/// messages in spoilers
+///
+/// updates that aren't edits, like the ones that add the embeds of links, are
+/// skipped so that the message isn't handled twice
+pub async fn edit_update(ctx: Context, update: MessageUpdate) -> Result<()> {
+    let (Some(guild_id), Some(author), Some(content), Some(_)) = (
+        update.guild_id,
+        &update.author,
+        &update.content,
+        update.edited_timestamp,
+    ) else {
+        return Ok(());
+    };
+    if author.bot
+        || ctx.editing.contains(&update.id)
+        || bypasses(&ctx, guild_id, author.id, &[]).await?
+        || filter::get_in_channel(&ctx, guild_id, update.channel_id)
+            .await?
+            .and_then(|filter| spoiler_words(&filter, content))
+            .is_none()
+        || !database::settings(&ctx.db, guild_id).await?.spoiler_edits
+    {
+        return Ok(());
+    }
+
+    let mut message = ctx
+        .http
+        .message(update.channel_id, update.id)
+        .exec()
+        .await?
+        .model()
+        .await?;
+    let member = ctx
+        .http
+        .guild_member(guild_id, message.author.id)
+        .exec()
+        .await?
+        .model()
+        .await?;
+
+    message.guild_id = Some(guild_id);
+    message.member = Some(PartialMember {
+        avatar: member.avatar,
+        communication_disabled_until: member.communication_disabled_until,
+        deaf: member.deaf,
+        joined_at: member.joined_at,
+        mute: member.mute,
+        nick: member.nick,
+        permissions: None,
+        premium_since: member.premium_since,
+        roles: member.roles,
+        user: Some(member.user),
+    });
+
+    edit(ctx, message).await
+}
+
+/// returns whether the user's messages are never put in spoilers, checking the
+/// cached roles of the member, or `roles` if the member isn't cached
+async fn bypasses(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    roles: &[Id<RoleMarker>],
+) -> Result<bool> {
+    let bypass = bypass::get(ctx, guild_id).await?;
+
+    Ok(ctx.cache.member(guild_id, user_id).map_or_else(
+        || bypass.contains(user_id, roles),
+        |member| bypass.contains(user_id, member.roles()),
+    ))
+}
+
+/// returns the quote to put at the start of the message to show which message
+/// it's replying to, since webhooks can't reply to messages
+fn reply_header(message: &Message, guild_id: Id<GuildMarker>) -> Option<String> {
+    if message.kind != MessageType::Reply {
+        return None;
+    }
+
+    let reference = message.reference.as_ref()?;
+    let url = format!(
+        "https://discord.com/channels/{guild_id}/{}/{}",
+        reference.channel_id?, reference.message_id?
+    );
+
+    Some(message.referenced_message.as_ref().map_or_else(
+        || format!("> replying to {url}\n"),
+        |referenced| format!("> replying to <@{}>: {url}\n", referenced.author.id),
+    ))
+}
+
+/// downloads the attachments of the message to upload them again, returns
+/// `None` if they're over the upload limit of the guild
+async fn attachments(
+    ctx: &Context,
+    message: &Message,
+    guild_id: Id<GuildMarker>,
+    spoiler: bool,
+) -> Result<Option<Vec<Attachment>>> {
+    if message
+        .attachments
+        .iter()
+        .map(|attachment| attachment.size)
+        .sum::<u64>()
+        > upload_limit(ctx, guild_id)
+    {
+        return Ok(None);
+    }
+
+    let files = future::try_join_all(
+        message
+            .attachments
+            .iter()
+            .map(|attachment| download(ctx, &attachment.url)),
+    )
+    .await?;
+
+    Ok(Some(
+        (0..)
+            .zip(message.attachments.iter().zip(files))
+            .map(|(id, (attachment, file))| {
+                let filename = if spoiler && !attachment.filename.starts_with("SPOILER_") {
+                    format!("SPOILER_{}", attachment.filename)
+                } else {
+                    attachment.filename.clone()
+                };
+                let mut reupload = Attachment::from_bytes(filename, file, id);
+                if let Some(description) = &attachment.description {
+                    reupload.description(description.clone());
+                }
+                reupload
+            })
+            .collect(),
+    ))
+}
+
+/// returns the maximum size of the files in a message in the guild in bytes
+#[allow(clippy::wildcard_enum_match_arm)]
+fn upload_limit(ctx: &Context, guild_id: Id<GuildMarker>) -> u64 {
+    match ctx.cache.guild(guild_id).map(|guild| guild.premium_tier()) {
+        Some(PremiumTier::Tier2) => 50 * 1024 * 1024,
+        Some(PremiumTier::Tier3) => 100 * 1024 * 1024,
+        _ => 25 * 1024 * 1024,
+    }
+}
+
+/// merges the overlapping and adjacent spans, so that each merged span can be
+/// put in a single spoiler
+fn merge(mut spans: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    spans.sort_unstable_by_key(|span| span.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+            _ => merged.push(span),
+        }
+    }
+
+    merged
+}
+
+/// wraps the given sorted and non-overlapping spans of `content` in spoiler
+/// tags
+#[allow(
+    clippy::integer_arithmetic,
+    clippy::string_slice,
+    clippy::indexing_slicing
+)]
+fn spoiler(content: &str, spans: &[Range<usize>]) -> String {
+    let mut spoilered = String::with_capacity(content.len() + spans.len() * 4);
+    let mut last = 0;
+
+    for span in spans {
+        spoilered.push_str(&content[last..span.start]);
+        spoilered.push_str("||");
+        spoilered.push_str(&content[span.clone()]);
+        spoilered.push_str("||");
+        last = span.end;
+    }
+    spoilered.push_str(&content[last..]);
+
+    spoilered
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::{merge, spoiler};
+
+    #[test]
+    fn overlapping_words() {
+        // "kill" and "killing" both match the start of "killing"
+        let spans = merge(vec![0..7, 0..4, 15..19]);
+
+        assert_eq!(spans, [0..7, 15..19]);
+        assert_eq!(
+            spoiler("killing me, or kill", &spans),
+            "||killing|| me, or ||kill||"
+        );
+    }
+
+    #[test]
+    fn nested_words() {
+        assert_eq!(merge(vec![2..5, 0..9, 4..6]), [0..9]);
+    }
+
+    #[test]
+    fn touching_words() {
+        let spans = merge(vec![5..9, 0..5]);
+
+        assert_eq!(spans, [0..9]);
+        assert_eq!(spoiler("selfkill", &[0..8]), "||selfkill||");
+    }
+
+    #[test]
+    fn separate_words() {
+        let spans = merge(vec![5..9, 0..4]);
+
+        assert_eq!(spans, [0..4, 5..9]);
+        assert_eq!(spoiler("kill kill", &spans), "||kill|| ||kill||");
+    }
+}
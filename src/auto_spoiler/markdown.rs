@@ -0,0 +1,173 @@
+use std::ops::Range;
+
+/// the prefixes of the `<...>` syntax discord uses for mentions, custom
+/// emojis, timestamps, slash commands and links without embeds
+const ANGLE_BRACKET_PREFIXES: [&str; 7] = ["@", "#", ":", "a:", "t:", "/", "http"];
+
+/// returns the spans of `content` that words shouldn't be put in spoilers in
+///
+/// these are code blocks, inline code, text that's already in spoilers,
+/// mentions, custom emojis and the rest of the `<...>` syntax, links and the
+/// links of masked links
+#[allow(
+    clippy::integer_arithmetic,
+    clippy::string_slice,
+    clippy::indexing_slicing
+)]
+pub fn protected(content: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut idx = 0;
+
+    while idx < content.len() {
+        let rest = &content[idx..];
+        if let Some(escaped) = rest.strip_prefix('\\') {
+            idx += 1 + escaped.chars().next().map_or(0, char::len_utf8);
+            continue;
+        }
+
+        let backticks = rest.len() - rest.trim_start_matches('`').len();
+        let (token_len, span_len) = if backticks > 0 {
+            let delimiter = &rest[..backticks.min(3)];
+            (backticks, delimited(rest, delimiter, delimiter))
+        } else if rest.starts_with("||") {
+            (2, delimited(rest, "||", "||"))
+        } else if rest.starts_with("](") {
+            (2, delimited(rest, "](", ")"))
+        } else if rest.starts_with('<') {
+            (1, angle_brackets(rest))
+        } else if rest.starts_with("http://") || rest.starts_with("https://") {
+            (
+                0,
+                Some(rest.find(char::is_whitespace).unwrap_or(rest.len())),
+            )
+        } else {
+            (rest.chars().next().map_or(1, char::len_utf8), None)
+        };
+
+        if let Some(len) = span_len {
+            spans.push(idx..idx + len);
+            idx += len;
+        } else {
+            idx += token_len;
+        }
+    }
+
+    spans
+}
+
+/// returns the length of the span starting with `start` at the start of `rest`
+/// and ending with `end`, if `end` exists
+#[allow(clippy::integer_arithmetic, clippy::string_slice)]
+fn delimited(rest: &str, start: &str, end: &str) -> Option<usize> {
+    rest[start.len()..]
+        .find(end)
+        .map(|len| start.len() + len + end.len())
+}
+
+/// returns the length of the `<...>` syntax at the start of `rest`, if it's one
+/// of the discord syntaxes
+#[allow(
+    clippy::integer_arithmetic,
+    clippy::string_slice,
+    clippy::indexing_slicing
+)]
+fn angle_brackets(rest: &str) -> Option<usize> {
+    let len = rest.find('>')? + 1;
+    let inner = &rest[1..len - 1];
+
+    (ANGLE_BRACKET_PREFIXES
+        .iter()
+        .any(|prefix| inner.starts_with(prefix))
+        && (inner.starts_with('/') || !inner.contains(char::is_whitespace)))
+    .then_some(len)
+}
+
+/// returns whether `span` overlaps with any of the `protected` spans
+pub fn is_protected(protected: &[Range<usize>], span: &Range<usize>) -> bool {
+    protected
+        .iter()
+        .any(|protected_span| protected_span.start < span.end && span.start < protected_span.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_protected, protected};
+
+    /// returns the protected parts of `content`
+    #[allow(clippy::indexing_slicing, clippy::string_slice)]
+    fn protected_texts(content: &str) -> Vec<&str> {
+        protected(content)
+            .into_iter()
+            .map(|span| &content[span])
+            .collect()
+    }
+
+    #[test]
+    fn code() {
+        assert_eq!(
+            protected_texts("a `kill` b ``k`ill`` c ```\nkill\n``` d"),
+            ["`kill`", "``k`ill``", "```\nkill\n```"]
+        );
+    }
+
+    #[test]
+    fn unclosed_code() {
+        assert!(protected_texts("a `kill").is_empty());
+    }
+
+    #[test]
+    fn mentions() {
+        assert_eq!(
+            protected_texts("<@123> <@&456> <#789> <:kill:1> <a:kill:2> <t:3:R> </kill:4>"),
+            [
+                "<@123>",
+                "<@&456>",
+                "<#789>",
+                "<:kill:1>",
+                "<a:kill:2>",
+                "<t:3:R>",
+                "</kill:4>"
+            ]
+        );
+    }
+
+    #[test]
+    fn other_angle_brackets() {
+        assert!(protected_texts("i <3 you > kill <not a mention>").is_empty());
+    }
+
+    #[test]
+    fn links() {
+        assert_eq!(
+            protected_texts("https://kill.com [kill](https://kill.com) <https://kill.com>"),
+            [
+                "https://kill.com",
+                "](https://kill.com)",
+                "<https://kill.com>"
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        assert!(protected_texts(r"\`kill\` \|\|kill\|\| \<@123>").is_empty());
+    }
+
+    #[test]
+    fn spoilers() {
+        assert_eq!(
+            protected_texts("||kill|| kill ||`||`"),
+            ["||kill||", "||`||"]
+        );
+    }
+
+    #[test]
+    fn overlapping_spans() {
+        let spans = protected("a ||kill|| b");
+
+        assert!(is_protected(&spans, &(4..8)));
+        assert!(is_protected(&spans, &(0..3)));
+        assert!(!is_protected(&spans, &(0..2)));
+        assert!(!is_protected(&spans, &(10..12)));
+    }
+}
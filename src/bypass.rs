@@ -0,0 +1,67 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use anyhow::Result;
+use dashmap::mapref::entry::Entry;
+use twilight_model::id::{
+    marker::{GuildMarker, RoleMarker, UserMarker},
+    Id,
+};
+
+use crate::{database, Context};
+
+/// the members whose messages are never put in spoilers in a guild, cached
+/// apart from the filter so that changing them doesn't rebuild it
+pub struct Bypass {
+    role_ids: Vec<Id<RoleMarker>>,
+    /// the users that opted out
+    user_ids: Vec<Id<UserMarker>>,
+}
+
+impl Bypass {
+    /// returns whether the messages of the member are never put in spoilers,
+    /// because they opted out or have one of the bypass roles
+    pub fn contains(&self, user_id: Id<UserMarker>, role_ids: &[Id<RoleMarker>]) -> bool {
+        self.user_ids.contains(&user_id)
+            || role_ids
+                .iter()
+                .any(|role_id| self.role_ids.contains(role_id))
+    }
+}
+
+/// returns the bypasses of the guild, fetching them from the database if they
+/// aren't cached
+///
+/// like the filters, they're only cached if they weren't invalidated while
+/// they were fetched
+pub async fn get(ctx: &Context, guild_id: Id<GuildMarker>) -> Result<Arc<Bypass>> {
+    if let Some(bypass) = ctx.bypasses.get(&guild_id) {
+        return Ok(Arc::clone(&bypass));
+    }
+
+    let generation = ctx.bypasses_generation.load(Ordering::SeqCst);
+    let bypass = Arc::new(Bypass {
+        role_ids: database::bypass_roles(&ctx.db, guild_id).await?,
+        user_ids: database::opted_out_users(&ctx.db, guild_id).await?,
+    });
+
+    let entry = ctx.bypasses.entry(guild_id);
+    if ctx.bypasses_generation.load(Ordering::SeqCst) == generation {
+        match entry {
+            Entry::Occupied(mut occupied) => {
+                occupied.insert(Arc::clone(&bypass));
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(Arc::clone(&bypass));
+            }
+        }
+    }
+
+    Ok(bypass)
+}
+
+/// removes the cached bypasses of the guild, should be called when its bypass
+/// roles change or a member opts out or back in
+pub fn invalidate(ctx: &Context, guild_id: Id<GuildMarker>) {
+    ctx.bypasses_generation.fetch_add(1, Ordering::SeqCst);
+    ctx.bypasses.remove(&guild_id);
+}
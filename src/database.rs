@@ -1,49 +1,261 @@
+use std::fmt::{self, Display, Formatter};
+
 use anyhow::Result;
-use sqlx::{query, query_as, PgPool};
-use twilight_model::id::{marker::GuildMarker, Id};
+use serde::Serialize;
+use sqlx::{
+    postgres::{PgHasArrayType, PgTypeInfo},
+    query, query_as, PgPool,
+};
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+    Id,
+};
 
 pub struct Word {
     pub id: i32,
+    /// the word without its wildcards, or the pattern if it's a regex
     pub word: String,
+    pub kind: WordKind,
+    /// whether to also match the plural, -ing and -ed forms of the word
+    pub inflections: bool,
+    /// the pack of the default word or the shared list of the word the guild
+    /// subscribed to, `None` if the word was added by the guild
+    pub origin: Option<String>,
 }
 
-#[allow(clippy::integer_arithmetic, clippy::panic)]
+impl Display for Word {
+    /// writes the word with its wildcards, the way it's added
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_word(f, &self.word, self.kind)
+    }
+}
+
+/// writes the word with the wildcards of its kind
+fn write_word(f: &mut Formatter<'_>, word: &str, kind: WordKind) -> fmt::Result {
+    match kind {
+        WordKind::Word | WordKind::Phrase => write!(f, "{word}"),
+        WordKind::Prefix => write!(f, "{word}*"),
+        WordKind::Suffix => write!(f, "*{word}"),
+        WordKind::Substring => write!(f, "*{word}*"),
+        WordKind::Regex => write!(f, "/{word}/"),
+    }
+}
+
+/// how a word is matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(type_name = "word_kind", rename_all = "lowercase")]
+pub enum WordKind {
+    /// matched when it's on its own
+    Word,
+    /// a few words matched with any spacing or punctuation between them
+    Phrase,
+    /// matched at the start of a word, added as `word*`
+    Prefix,
+    /// matched at the end of a word, added as `*word`
+    Suffix,
+    /// matched anywhere, even inside other words, added as `*word*`
+    Substring,
+    /// a pattern matched case insensitively against the original message,
+    /// added as `/pattern/`
+    Regex,
+}
+
+impl WordKind {
+    /// parses a word added with wildcards, such as `suicid*`, returning the
+    /// lowercased word without the wildcards and with single spaces between
+    /// its words, and its kind
+    ///
+    /// regexes are written between slashes and returned as they are
+    pub fn parse(entry: &str) -> (String, Self) {
+        let trimmed = entry.trim();
+        if let Some(pattern) = trimmed
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+        {
+            return (pattern.to_owned(), Self::Regex);
+        }
+        let word = trimmed
+            .trim_matches('*')
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+
+        let kind = match (trimmed.starts_with('*'), trimmed.ends_with('*')) {
+            (true, true) => Self::Substring,
+            (false, true) => Self::Prefix,
+            (true, false) => Self::Suffix,
+            (false, false) if word.contains(char::is_whitespace) => Self::Phrase,
+            (false, false) => Self::Word,
+        };
+
+        (word, kind)
+    }
+}
+
+impl PgHasArrayType for WordKind {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("_word_kind")
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        *ty == Self::array_type_info()
+    }
+}
+
+/// returns the guild's custom words, the default words in the packs it enabled
+/// and the words of the shared lists it subscribed to, except the ones it
+/// allowed
 pub async fn words(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<Vec<Word>> {
+    words_in(db, guild_id, None).await
+}
+
+/// returns the guild's words like [`words`], with the overrides of the channel
+/// settings of `channel_id` if it's given
+///
+/// the channel's words are added to the guild's words, and if the channel has
+/// packs, only the default words in them are used
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn words_in(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    channel_id: Option<Id<ChannelMarker>>,
+) -> Result<Vec<Word>> {
     Ok(query_as!(
         Word,
         r#"
         SELECT
-            id AS "id!",
-            word AS "word!"
+            words.id AS "id!",
+            word AS "word!",
+            kind AS "kind!: WordKind",
+            inflections AS "inflections!",
+            COALESCE(packs.name, 'shared list ' || shared_lists.code) AS "origin?"
         FROM
             words
-        WHERE
-            guild_id = $1
-            OR guild_id IS NULL
+            LEFT JOIN packs ON packs.id = words.pack_id
+            LEFT JOIN guild_packs ON guild_packs.pack_id = packs.id
+                AND guild_packs.guild_id = $1
+            LEFT JOIN shared_lists ON shared_lists.guild_id = words.guild_id
+                AND words.guild_id <> $1
+        WHERE (words.guild_id = $1
+            AND (words.channel_id IS NULL
+                OR words.channel_id = $2)
+            OR words.guild_id IS NULL
+            AND CASE WHEN EXISTS (
+                SELECT
+                    1
+                FROM
+                    channel_packs
+                WHERE
+                    channel_packs.channel_id = $2) THEN
+                words.pack_id IN (
+                    SELECT
+                        pack_id
+                    FROM
+                        channel_packs
+                    WHERE
+                        channel_packs.channel_id = $2)
+            ELSE
+                COALESCE(guild_packs.enabled, packs.enabled_by_default)
+            END
+            OR words.channel_id IS NULL
+            AND words.guild_id IN (
+                SELECT
+                    publisher_id
+                FROM
+                    subscriptions
+                WHERE
+                    subscriptions.guild_id = $1))
             AND NOT EXISTS (
                 SELECT
                     1
                 FROM
                     allowed_words
                 WHERE
-                    guild_id = $1
-                    AND word_id = id);
+                    allowed_words.guild_id = $1
+                    AND word_id = words.id);
         "#,
-        encode(guild_id)
+        encode(guild_id),
+        channel_id.map(encode)
     )
     .fetch_all(db)
     .await?)
 }
 
+#[derive(Serialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Settings {
+    pub spoiler_attachments: bool,
+    pub reply_context: bool,
+    pub spoiler_edits: bool,
+    /// whether to normalize messages before matching words to catch obfuscated
+    /// words
+    pub normalize: bool,
+    /// the characters to replace when normalizing, see
+    /// [`crate::filter::normalize::Normalization::new`]
+    pub leet_map: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            spoiler_attachments: false,
+            reply_context: true,
+            spoiler_edits: false,
+            normalize: false,
+            leet_map: "4a 3e 1i 0o 5s 7t @a $s".to_owned(),
+        }
+    }
+}
+
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn settings(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<Settings> {
+    Ok(query_as!(
+        Settings,
+        r#"
+        SELECT
+            spoiler_attachments,
+            reply_context,
+            spoiler_edits,
+            normalize,
+            leet_map
+        FROM
+            guild_settings
+        WHERE
+            guild_id = $1
+        "#,
+        encode(guild_id)
+    )
+    .fetch_optional(db)
+    .await?
+    .unwrap_or_default())
+}
+
 #[allow(clippy::integer_arithmetic, clippy::panic)]
-pub async fn add_custom_word(db: &PgPool, guild_id: Id<GuildMarker>, word: String) -> Result<()> {
+pub async fn set_settings(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    settings: &Settings,
+) -> Result<()> {
     query!(
         r#"
-        INSERT INTO words (guild_id, word)
-            VALUES ($1, $2)
+        INSERT INTO guild_settings (guild_id, spoiler_attachments, reply_context, spoiler_edits,
+            normalize, leet_map)
+            VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (guild_id)
+            DO UPDATE SET
+                spoiler_attachments = $2,
+                reply_context = $3,
+                spoiler_edits = $4,
+                normalize = $5,
+                leet_map = $6
         "#,
         encode(guild_id),
-        word
+        settings.spoiler_attachments,
+        settings.reply_context,
+        settings.spoiler_edits,
+        settings.normalize,
+        settings.leet_map
     )
     .execute(db)
     .await?;
@@ -51,37 +263,1402 @@ pub async fn add_custom_word(db: &PgPool, guild_id: Id<GuildMarker>, word: Strin
     Ok(())
 }
 
+/// adds the custom word, returning `false` if the guild already added it
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn add_custom_word(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    word: String,
+    kind: WordKind,
+    inflections: bool,
+) -> Result<bool> {
+    let added = query!(
+        r#"
+        INSERT INTO words (guild_id, word, kind, inflections)
+            VALUES ($1, $2, $3, $4)
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        encode(guild_id),
+        word,
+        kind as _,
+        inflections
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    Ok(added > 0)
+}
+
+/// returns the number of words the guild added, including the words added
+/// only to its channels
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn custom_word_count(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<usize> {
+    let count = query!(
+        r#"
+        SELECT
+            COUNT(*) AS "count!"
+        FROM
+            words
+        WHERE
+            guild_id = $1
+        "#,
+        encode(guild_id)
+    )
+    .fetch_one(db)
+    .await?
+    .count;
+
+    Ok(usize::try_from(count)?)
+}
+
+/// returns the number of regexes the guild added, including the regexes added
+/// only to its channels
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn regex_word_count(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<usize> {
+    let count = query!(
+        r#"
+        SELECT
+            COUNT(*) AS "count!"
+        FROM
+            words
+        WHERE
+            guild_id = $1
+            AND kind = 'regex'
+        "#,
+        encode(guild_id)
+    )
+    .fetch_one(db)
+    .await?
+    .count;
+
+    Ok(usize::try_from(count)?)
+}
+
+/// adds the custom words and whether to match their inflections, returning
+/// how many the guild didn't already add
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn add_custom_words(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    new_words: &[(String, WordKind, bool)],
+) -> Result<u64> {
+    let mut words = Vec::with_capacity(new_words.len());
+    let mut kinds = Vec::with_capacity(new_words.len());
+    let mut inflections = Vec::with_capacity(new_words.len());
+    for (word, kind, word_inflections) in new_words {
+        words.push(word.clone());
+        kinds.push(*kind);
+        inflections.push(*word_inflections);
+    }
+
+    Ok(query!(
+        r#"
+        INSERT INTO words (guild_id, word, kind, inflections)
+            SELECT $1, * FROM UNNEST($2::text[], $3::word_kind[], $4::bool[])
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        encode(guild_id),
+        &words,
+        kinds as _,
+        &inflections
+    )
+    .execute(db)
+    .await?
+    .rows_affected())
+}
+
+/// adds the default word to the pack, creating the pack if it doesn't exist,
+/// returning `false` if it's already a default word
 #[allow(clippy::integer_arithmetic, clippy::panic)]
-pub async fn add_default_word(db: &PgPool, word: String) -> Result<()> {
+pub async fn add_default_word(
+    db: &PgPool,
+    word: String,
+    kind: WordKind,
+    inflections: bool,
+    pack: &str,
+) -> Result<bool> {
     query!(
         r#"
-        INSERT INTO words (word)
+        INSERT INTO packs (name)
             VALUES ($1)
+        ON CONFLICT
+            DO NOTHING
         "#,
-        word
+        pack
     )
     .execute(db)
     .await?;
 
-    Ok(())
+    let added = query!(
+        r#"
+        INSERT INTO words (word, kind, inflections, pack_id)
+        SELECT
+            $1,
+            $2,
+            $3,
+            id
+        FROM
+            packs
+        WHERE
+            name = $4
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        word,
+        kind as _,
+        inflections,
+        pack
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    Ok(added > 0)
+}
+
+/// returns the default words in every pack, with their pack as their origin
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn default_words(db: &PgPool) -> Result<Vec<Word>> {
+    Ok(query_as!(
+        Word,
+        r#"
+        SELECT
+            words.id,
+            word,
+            kind AS "kind!: WordKind",
+            inflections,
+            packs.name AS "origin?"
+        FROM
+            words
+            JOIN packs ON packs.id = words.pack_id
+        ORDER BY
+            packs.name,
+            word
+        "#
+    )
+    .fetch_all(db)
+    .await?)
+}
+
+/// removes the default word, returns whether it existed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn remove_default_word(db: &PgPool, word: &str, kind: WordKind) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM words
+        WHERE guild_id IS NULL
+            AND word = $1
+            AND kind = $2
+        "#,
+        word,
+        kind as _
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// changes the default word, keeping it allowed in the guilds that allowed it,
+/// returns whether it existed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn rename_default_word(
+    db: &PgPool,
+    word: &str,
+    kind: WordKind,
+    new_word: String,
+    new_kind: WordKind,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        UPDATE
+            words
+        SET
+            word = $3,
+            kind = $4
+        WHERE
+            guild_id IS NULL
+            AND word = $1
+            AND kind = $2
+        "#,
+        word,
+        kind as _,
+        new_word,
+        new_kind as _
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
 }
 
-pub async fn delete_word(db: &PgPool, id: i32) -> Result<()> {
-    #[allow(clippy::panic, clippy::integer_arithmetic)]
+/// moves the default word to the pack, creating the pack if it doesn't exist,
+/// returns whether the word existed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn move_default_word(
+    db: &PgPool,
+    word: &str,
+    kind: WordKind,
+    pack: &str,
+) -> Result<bool> {
     query!(
+        r#"
+        INSERT INTO packs (name)
+            VALUES ($1)
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        pack
+    )
+    .execute(db)
+    .await?;
+
+    Ok(query!(
+        r#"
+        UPDATE
+            words
+        SET
+            pack_id = packs.id
+        FROM
+            packs
+        WHERE
+            packs.name = $3
+            AND words.guild_id IS NULL
+            AND words.word = $1
+            AND words.kind = $2
+        "#,
+        word,
+        kind as _,
+        pack
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// returns the guilds that allowed the default word
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn allowing_guilds(
+    db: &PgPool,
+    word: &str,
+    kind: WordKind,
+) -> Result<Vec<Id<GuildMarker>>> {
+    Ok(query!(
+        r#"
+        SELECT
+            allowed_words.guild_id
+        FROM
+            allowed_words
+            JOIN words ON words.id = allowed_words.word_id
+        WHERE
+            words.guild_id IS NULL
+            AND words.word = $1
+            AND words.kind = $2
+        "#,
+        word,
+        kind as _
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .filter_map(|row| decode(row.guild_id))
+    .collect())
+}
+
+/// allows the word in the guild, deleting it if it's a custom word of the guild
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn allow_word(db: &PgPool, guild_id: Id<GuildMarker>, word_id: i32) -> Result<()> {
+    let deleted = query!(
         r#"
         DELETE FROM words
         WHERE id = $1
+            AND guild_id = $2
         "#,
-        id
+        word_id,
+        encode(guild_id)
     )
     .execute(db)
-    .await?;
+    .await?
+    .rows_affected();
+
+    if deleted == 0 {
+        query!(
+            r#"
+            INSERT INTO allowed_words (guild_id, word_id)
+                VALUES ($1, $2)
+            ON CONFLICT
+                DO NOTHING
+            "#,
+            encode(guild_id),
+            word_id
+        )
+        .execute(db)
+        .await?;
+    }
 
     Ok(())
 }
 
-#[allow(clippy::cast_possible_wrap, clippy::as_conversions)]
-const fn encode<T>(id: Id<T>) -> i64 {
-    id.get() as i64
+/// returns the default and shared words the guild allowed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn allowed_words(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<Vec<Word>> {
+    Ok(query_as!(
+        Word,
+        r#"
+        SELECT
+            words.id AS "id!",
+            word AS "word!",
+            kind AS "kind!: WordKind",
+            inflections AS "inflections!",
+            COALESCE(packs.name, 'shared list ' || shared_lists.code) AS "origin?"
+        FROM
+            words
+            JOIN allowed_words ON word_id = words.id
+            LEFT JOIN packs ON packs.id = words.pack_id
+            LEFT JOIN shared_lists ON shared_lists.guild_id = words.guild_id
+        WHERE
+            allowed_words.guild_id = $1
+        "#,
+        encode(guild_id)
+    )
+    .fetch_all(db)
+    .await?)
+}
+
+/// deletes the custom word of the guild, returns whether it existed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn remove_custom_word(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    word: &str,
+    kind: WordKind,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM words
+        WHERE guild_id = $1
+            AND channel_id IS NULL
+            AND word = $2
+            AND kind = $3
+        "#,
+        encode(guild_id),
+        word,
+        kind as _
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// puts the allowed default or shared word back in the guild's list, returns
+/// whether it was allowed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn unallow_word(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    word: &str,
+    kind: WordKind,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM allowed_words
+        WHERE guild_id = $1
+            AND word_id IN (
+                SELECT
+                    id
+                FROM
+                    words
+                WHERE
+                    word = $2
+                    AND kind = $3)
+        "#,
+        encode(guild_id),
+        word,
+        kind as _
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+pub struct Pack {
+    pub name: String,
+    pub description: String,
+    /// whether the words in the pack are put in spoilers in the guild
+    pub enabled: bool,
+    pub word_count: i64,
+}
+
+/// returns all the packs, with whether they're enabled in the guild
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn packs(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<Vec<Pack>> {
+    Ok(query_as!(
+        Pack,
+        r#"
+        SELECT
+            name,
+            description,
+            COALESCE(guild_packs.enabled, enabled_by_default) AS "enabled!",
+            (
+                SELECT
+                    COUNT(*)
+                FROM
+                    words
+                WHERE
+                    words.pack_id = packs.id) AS "word_count!"
+        FROM
+            packs
+            LEFT JOIN guild_packs ON guild_packs.pack_id = packs.id
+                AND guild_packs.guild_id = $1
+        ORDER BY
+            name
+        "#,
+        encode(guild_id)
+    )
+    .fetch_all(db)
+    .await?)
+}
+
+/// returns the names of all the packs, including the ones without words
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn pack_names(db: &PgPool) -> Result<Vec<String>> {
+    Ok(query!(
+        r#"
+        SELECT
+            name
+        FROM
+            packs
+        ORDER BY
+            name
+        "#
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|row| row.name)
+    .collect())
+}
+
+/// enables or disables the pack in the guild, returns whether the pack exists
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn set_pack_enabled(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    pack: &str,
+    enabled: bool,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        INSERT INTO guild_packs (guild_id, pack_id, enabled)
+        SELECT
+            $1,
+            id,
+            $3
+        FROM
+            packs
+        WHERE
+            name = $2
+        ON CONFLICT (guild_id, pack_id)
+            DO UPDATE SET
+                enabled = $3
+        "#,
+        encode(guild_id),
+        pack,
+        enabled
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// returns the share code of the guild's list, if it published it
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn share_code(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<Option<String>> {
+    Ok(query!(
+        r#"
+        SELECT
+            code
+        FROM
+            shared_lists
+        WHERE
+            guild_id = $1
+        "#,
+        encode(guild_id)
+    )
+    .fetch_optional(db)
+    .await?
+    .map(|row| row.code))
+}
+
+/// publishes the guild's custom words under the code, returns the code the
+/// list is published under, which is the old one if it was already published
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn publish(db: &PgPool, guild_id: Id<GuildMarker>, code: &str) -> Result<String> {
+    Ok(query!(
+        r#"
+        INSERT INTO shared_lists (guild_id, code)
+            VALUES ($1, $2)
+        ON CONFLICT (guild_id)
+            DO UPDATE SET
+                guild_id = EXCLUDED.guild_id
+            RETURNING
+                code
+        "#,
+        encode(guild_id),
+        code
+    )
+    .fetch_one(db)
+    .await?
+    .code)
+}
+
+/// stops sharing the guild's list, also removing its subscriptions, returns
+/// whether it was published
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn unpublish(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM shared_lists
+        WHERE guild_id = $1
+        "#,
+        encode(guild_id)
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// returns the guild that published its list under the code
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn publisher(db: &PgPool, code: &str) -> Result<Option<Id<GuildMarker>>> {
+    Ok(query!(
+        r#"
+        SELECT
+            guild_id
+        FROM
+            shared_lists
+        WHERE
+            code = $1
+        "#,
+        code
+    )
+    .fetch_optional(db)
+    .await?
+    .and_then(|row| decode(row.guild_id)))
+}
+
+pub struct Subscription {
+    pub publisher_id: Id<GuildMarker>,
+    pub code: String,
+}
+
+/// returns the shared lists the guild subscribed to
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn subscriptions(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<Vec<Subscription>> {
+    Ok(query!(
+        r#"
+        SELECT
+            publisher_id,
+            code
+        FROM
+            subscriptions
+            JOIN shared_lists ON shared_lists.guild_id = publisher_id
+        WHERE
+            subscriptions.guild_id = $1
+        "#,
+        encode(guild_id)
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .filter_map(|row| {
+        Some(Subscription {
+            publisher_id: decode(row.publisher_id)?,
+            code: row.code,
+        })
+    })
+    .collect())
+}
+
+/// subscribes the guild to the list of the publisher, returns whether it
+/// wasn't already subscribed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn subscribe(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    publisher_id: Id<GuildMarker>,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        INSERT INTO subscriptions (guild_id, publisher_id)
+            VALUES ($1, $2)
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        encode(guild_id),
+        encode(publisher_id)
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// unsubscribes the guild from the list with the code, returns whether it was
+/// subscribed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn unsubscribe(db: &PgPool, guild_id: Id<GuildMarker>, code: &str) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM subscriptions
+        WHERE guild_id = $1
+            AND publisher_id IN (
+                SELECT
+                    guild_id
+                FROM
+                    shared_lists
+                WHERE
+                    code = $2)
+        "#,
+        encode(guild_id),
+        code
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// returns the guild's words that are never put in spoilers
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn exceptions(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<Vec<String>> {
+    Ok(query!(
+        r#"
+        SELECT
+            exception
+        FROM
+            exceptions
+        WHERE
+            guild_id = $1
+        "#,
+        encode(guild_id)
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|row| row.exception)
+    .collect())
+}
+
+/// adds the exception to the guild, returns whether it wasn't already added
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn add_exception(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    exception: &str,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        INSERT INTO exceptions (guild_id, exception)
+            VALUES ($1, $2)
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        encode(guild_id),
+        exception
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// adds the exceptions to the guild, returns how many weren't already added
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn add_exceptions(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    exceptions: &[String],
+) -> Result<u64> {
+    Ok(query!(
+        r#"
+        INSERT INTO exceptions (guild_id, exception)
+            SELECT $1, * FROM UNNEST($2::text[])
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        encode(guild_id),
+        exceptions
+    )
+    .execute(db)
+    .await?
+    .rows_affected())
+}
+
+/// removes the exception from the guild, returns whether it existed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn remove_exception(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    exception: &str,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM exceptions
+        WHERE guild_id = $1
+            AND exception = $2
+        "#,
+        encode(guild_id),
+        exception
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// a word a guild suggested adding to the default words
+pub struct Suggestion {
+    pub word: String,
+    pub kind: WordKind,
+    pub inflections: bool,
+}
+
+impl Display for Suggestion {
+    /// writes the word with its wildcards, the way it's added
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_word(f, &self.word, self.kind)
+    }
+}
+
+/// whether a suggestion was reviewed
+#[derive(Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "suggestion_status", rename_all = "lowercase")]
+pub enum SuggestionStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// records the guild's suggestion, returns its id if no guild suggested the
+/// word before
+///
+/// `channel_id` is the channel to notify when the suggestion is approved
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn suggest(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    suggestion: &Suggestion,
+) -> Result<Option<i32>> {
+    let created_id = query!(
+        r#"
+        INSERT INTO suggestions (word, kind, inflections)
+            VALUES ($1, $2, $3)
+        ON CONFLICT
+            DO NOTHING
+        RETURNING
+            id
+        "#,
+        suggestion.word,
+        suggestion.kind as _,
+        suggestion.inflections
+    )
+    .fetch_optional(db)
+    .await?
+    .map(|row| row.id);
+
+    query!(
+        r#"
+        INSERT INTO suggesting_guilds (suggestion_id, guild_id, channel_id)
+        SELECT
+            id,
+            $3,
+            $4
+        FROM
+            suggestions
+        WHERE
+            word = $1
+            AND kind = $2
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        suggestion.word,
+        suggestion.kind as _,
+        encode(guild_id),
+        encode(channel_id)
+    )
+    .execute(db)
+    .await?;
+
+    Ok(created_id)
+}
+
+/// sets the status of the pending suggestion, returns it if it wasn't
+/// already reviewed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn review_suggestion(
+    db: &PgPool,
+    suggestion_id: i32,
+    status: SuggestionStatus,
+) -> Result<Option<Suggestion>> {
+    Ok(query_as!(
+        Suggestion,
+        r#"
+        UPDATE
+            suggestions
+        SET
+            status = $2
+        WHERE
+            id = $1
+            AND status = 'pending'
+        RETURNING
+            word,
+            kind AS "kind!: WordKind",
+            inflections
+        "#,
+        suggestion_id,
+        status as _
+    )
+    .fetch_optional(db)
+    .await?)
+}
+
+/// returns the channels to notify about the suggestion
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn suggesting_channels(
+    db: &PgPool,
+    suggestion_id: i32,
+) -> Result<Vec<Id<ChannelMarker>>> {
+    Ok(query!(
+        r#"
+        SELECT
+            channel_id
+        FROM
+            suggesting_guilds
+        WHERE
+            suggestion_id = $1
+        "#,
+        suggestion_id
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .filter_map(|row| decode(row.channel_id))
+    .collect())
+}
+
+/// the settings of a channel or category that override the guild's
+pub struct ChannelSettings {
+    pub channel_id: Id<ChannelMarker>,
+    /// whether words are put in spoilers in the channel, `None` if it's the
+    /// same as in its category
+    pub enabled: Option<bool>,
+}
+
+/// returns the settings of the given channels, skipping the channels without
+/// settings
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn channel_settings(
+    db: &PgPool,
+    channel_ids: &[Id<ChannelMarker>],
+) -> Result<Vec<ChannelSettings>> {
+    Ok(query!(
+        r#"
+        SELECT
+            channel_id,
+            enabled
+        FROM
+            channel_settings
+        WHERE
+            channel_id = ANY ($1)
+        "#,
+        &channel_ids.iter().copied().map(encode).collect::<Vec<_>>()
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .filter_map(|row| {
+        Some(ChannelSettings {
+            channel_id: decode(row.channel_id)?,
+            enabled: row.enabled,
+        })
+    })
+    .collect())
+}
+
+/// returns the settings of the guild's channels and categories
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn guild_channel_settings(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+) -> Result<Vec<ChannelSettings>> {
+    Ok(query!(
+        r#"
+        SELECT
+            channel_id,
+            enabled
+        FROM
+            channel_settings
+        WHERE
+            guild_id = $1
+        ORDER BY
+            channel_id
+        "#,
+        encode(guild_id)
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .filter_map(|row| {
+        Some(ChannelSettings {
+            channel_id: decode(row.channel_id)?,
+            enabled: row.enabled,
+        })
+    })
+    .collect())
+}
+
+/// gives the channel settings if it doesn't have any, so that its packs or
+/// words can be added, leaving whether it's enabled to its category
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+async fn create_channel_settings(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+) -> Result<()> {
+    query!(
+        r#"
+        INSERT INTO channel_settings (channel_id, guild_id)
+            VALUES ($1, $2)
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        encode(channel_id),
+        encode(guild_id)
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn set_channel_enabled(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    enabled: bool,
+) -> Result<()> {
+    query!(
+        r#"
+        INSERT INTO channel_settings (channel_id, guild_id, enabled)
+            VALUES ($1, $2, $3)
+        ON CONFLICT (channel_id)
+            DO UPDATE SET
+                enabled = $3
+        "#,
+        encode(channel_id),
+        encode(guild_id),
+        enabled
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// deletes the channel's settings, its packs and its words, returns whether it
+/// had settings
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn reset_channel(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM channel_settings
+        WHERE guild_id = $1
+            AND channel_id = $2
+        "#,
+        encode(guild_id),
+        encode(channel_id)
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// returns the names of the only packs used in the channel
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn channel_packs(db: &PgPool, channel_id: Id<ChannelMarker>) -> Result<Vec<String>> {
+    Ok(query!(
+        r#"
+        SELECT
+            name
+        FROM
+            channel_packs
+            JOIN packs ON packs.id = channel_packs.pack_id
+        WHERE
+            channel_id = $1
+        ORDER BY
+            name
+        "#,
+        encode(channel_id)
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|row| row.name)
+    .collect())
+}
+
+/// adds the pack to the packs used in the channel, returns whether the pack
+/// exists and wasn't already added
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn add_channel_pack(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    pack: &str,
+) -> Result<bool> {
+    create_channel_settings(db, guild_id, channel_id).await?;
+
+    Ok(query!(
+        r#"
+        INSERT INTO channel_packs (channel_id, pack_id)
+        SELECT
+            $1,
+            id
+        FROM
+            packs
+        WHERE
+            name = $2
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        encode(channel_id),
+        pack
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// removes the pack from the packs used in the channel, returns whether it was
+/// added
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn remove_channel_pack(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    pack: &str,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM channel_packs
+        WHERE channel_id IN (
+                SELECT
+                    channel_id
+                FROM
+                    channel_settings
+                WHERE
+                    guild_id = $1
+                    AND channel_id = $2)
+            AND pack_id IN (
+                SELECT
+                    id
+                FROM
+                    packs
+                WHERE
+                    name = $3)
+        "#,
+        encode(guild_id),
+        encode(channel_id),
+        pack
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// returns the words added only to the channel
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn channel_words(db: &PgPool, channel_id: Id<ChannelMarker>) -> Result<Vec<Word>> {
+    Ok(query_as!(
+        Word,
+        r#"
+        SELECT
+            id,
+            word,
+            kind AS "kind!: WordKind",
+            inflections,
+            NULL AS "origin?"
+        FROM
+            words
+        WHERE
+            channel_id = $1
+        ORDER BY
+            word
+        "#,
+        encode(channel_id)
+    )
+    .fetch_all(db)
+    .await?)
+}
+
+/// adds the word only to the channel, returns whether it wasn't already added
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn add_channel_word(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    word: String,
+    kind: WordKind,
+) -> Result<bool> {
+    create_channel_settings(db, guild_id, channel_id).await?;
+
+    Ok(query!(
+        r#"
+        INSERT INTO words (guild_id, channel_id, word, kind)
+            VALUES ($1, $2, $3, $4)
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        encode(guild_id),
+        encode(channel_id),
+        word,
+        kind as _
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// removes the word from the channel, returns whether it was added
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn remove_channel_word(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    word: &str,
+    kind: WordKind,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM words
+        WHERE guild_id = $1
+            AND channel_id = $2
+            AND word = $3
+            AND kind = $4
+        "#,
+        encode(guild_id),
+        encode(channel_id),
+        word,
+        kind as _
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// returns the roles whose members' messages are never put in spoilers in the
+/// guild
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn bypass_roles(db: &PgPool, guild_id: Id<GuildMarker>) -> Result<Vec<Id<RoleMarker>>> {
+    Ok(query!(
+        r#"
+        SELECT
+            role_id
+        FROM
+            bypass_roles
+        WHERE
+            guild_id = $1
+        "#,
+        encode(guild_id)
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .filter_map(|row| decode(row.role_id))
+    .collect())
+}
+
+/// adds the bypass role to the guild, returns whether it wasn't already added
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn add_bypass_role(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    role_id: Id<RoleMarker>,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        INSERT INTO bypass_roles (guild_id, role_id)
+            VALUES ($1, $2)
+        ON CONFLICT
+            DO NOTHING
+        "#,
+        encode(guild_id),
+        encode(role_id)
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// removes the bypass role from the guild, returns whether it was added
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn remove_bypass_role(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    role_id: Id<RoleMarker>,
+) -> Result<bool> {
+    Ok(query!(
+        r#"
+        DELETE FROM bypass_roles
+        WHERE guild_id = $1
+            AND role_id = $2
+        "#,
+        encode(guild_id),
+        encode(role_id)
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
+
+/// returns the users that opted out of having their messages put in spoilers
+/// in the guild
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn opted_out_users(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+) -> Result<Vec<Id<UserMarker>>> {
+    Ok(query!(
+        r#"
+        SELECT
+            user_id
+        FROM
+            opted_out_users
+        WHERE
+            guild_id = $1
+        "#,
+        encode(guild_id)
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .filter_map(|row| decode(row.user_id))
+    .collect())
+}
+
+/// sets whether the user opted out in the guild, returns whether it changed
+#[allow(clippy::integer_arithmetic, clippy::panic)]
+pub async fn set_opted_out(
+    db: &PgPool,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    opted_out: bool,
+) -> Result<bool> {
+    let changed = if opted_out {
+        query!(
+            r#"
+            INSERT INTO opted_out_users (guild_id, user_id)
+                VALUES ($1, $2)
+            ON CONFLICT
+                DO NOTHING
+            "#,
+            encode(guild_id),
+            encode(user_id)
+        )
+        .execute(db)
+        .await?
+    } else {
+        query!(
+            r#"
+            DELETE FROM opted_out_users
+            WHERE guild_id = $1
+                AND user_id = $2
+            "#,
+            encode(guild_id),
+            encode(user_id)
+        )
+        .execute(db)
+        .await?
+    };
+
+    Ok(changed.rows_affected() > 0)
+}
+
+#[allow(clippy::cast_possible_wrap, clippy::as_conversions)]
+const fn encode<T>(id: Id<T>) -> i64 {
+    id.get() as i64
+}
+
+#[allow(clippy::cast_sign_loss, clippy::as_conversions)]
+const fn decode<T>(id: i64) -> Option<Id<T>> {
+    Id::new_checked(id as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WordKind;
+
+    #[test]
+    fn wildcards() {
+        assert_eq!(
+            WordKind::parse("suicid*"),
+            ("suicid".to_owned(), WordKind::Prefix)
+        );
+        assert_eq!(
+            WordKind::parse("*cide"),
+            ("cide".to_owned(), WordKind::Suffix)
+        );
+        assert_eq!(
+            WordKind::parse("*word*"),
+            ("word".to_owned(), WordKind::Substring)
+        );
+        assert_eq!(
+            WordKind::parse(" Word "),
+            ("word".to_owned(), WordKind::Word)
+        );
+    }
+
+    #[test]
+    fn phrases() {
+        assert_eq!(
+            WordKind::parse("Kill   \tYourself"),
+            ("kill yourself".to_owned(), WordKind::Phrase)
+        );
+        assert_eq!(
+            WordKind::parse("*kill your*"),
+            ("kill your".to_owned(), WordKind::Substring)
+        );
+    }
+
+    #[test]
+    fn regexes() {
+        assert_eq!(
+            WordKind::parse(" /K[i1]ll\\s+/ "),
+            ("K[i1]ll\\s+".to_owned(), WordKind::Regex)
+        );
+        assert_eq!(WordKind::parse("/"), ("/".to_owned(), WordKind::Word));
+        assert_eq!(WordKind::parse("//"), (String::new(), WordKind::Regex));
+    }
 }
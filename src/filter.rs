@@ -0,0 +1,684 @@
+use std::{
+    hash::Hash,
+    ops::Range,
+    sync::{atomic::Ordering, Arc},
+};
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use anyhow::{Context as _, Result};
+use dashmap::{mapref::entry::Entry, DashMap};
+use regex::{Regex, RegexBuilder};
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker},
+    Id,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    channel_pair,
+    database::{self, ChannelSettings, Word, WordKind},
+    filter::normalize::{Folded, Normalization},
+    Context,
+};
+
+pub mod normalize;
+
+/// the maximum size of a compiled regex word in bytes
+const REGEX_SIZE_LIMIT: usize = 256 * 1024;
+
+/// the maximum nesting depth of a regex word
+const REGEX_NEST_LIMIT: u32 = 16;
+
+/// the maximum size in bytes of the regex all the regex words of a filter are
+/// compiled into
+const REGEXES_SIZE_LIMIT: usize = 2 * 1024 * 1024;
+
+/// the words to put in spoilers in a guild, compiled into a single automaton
+/// and a single regex for the regex words
+pub struct Filter {
+    words: Vec<Word>,
+    /// the pattern of each form of the words in the automaton
+    patterns: Vec<Pattern>,
+    automaton: AhoCorasick,
+    /// the regex words joined by alternation, each in its own group
+    regex: Option<Regex>,
+    /// the index of each regex word's group in `regex` and its index in
+    /// `words`
+    regex_groups: Vec<(usize, usize)>,
+    /// the guild's words that are never put in spoilers, matches that are
+    /// inside one of them are skipped
+    exceptions: AhoCorasick,
+    /// the normalization to apply to messages, if the guild enabled it
+    normalization: Option<Normalization>,
+    /// the guilds whose shared lists the guild subscribed to, the filter is
+    /// invalidated when their words change
+    publishers: Vec<Id<GuildMarker>>,
+}
+
+/// a form of a word in the automaton
+struct Pattern {
+    /// the index of the word in [`Filter::words`]
+    word: usize,
+    /// the runs of the folded form, see [`Folded::runs`]
+    runs: Vec<usize>,
+}
+
+/// an occurrence of a word in a message
+pub struct Match<'filter> {
+    pub word: &'filter Word,
+    /// the span of the occurrence in the original content
+    pub span: Range<usize>,
+}
+
+impl Filter {
+    #[allow(clippy::integer_arithmetic)]
+    fn new(
+        words: Vec<Word>,
+        exceptions: &[String],
+        normalization: Option<Normalization>,
+        publishers: Vec<Id<GuildMarker>>,
+    ) -> Result<Self> {
+        let mut texts = vec![];
+        let mut patterns = vec![];
+        let mut alternatives = vec![];
+        let mut regex_groups = vec![];
+        // group 0 is the whole match
+        let mut group = 1;
+        for (idx, word) in words.iter().enumerate() {
+            if word.kind == WordKind::Regex {
+                // regexes are validated when they're added, compiling them here
+                // only counts their groups to know where the next one starts
+                if let Ok(compiled) = regex(&word.word) {
+                    alternatives.push(format!("({})", word.word));
+                    regex_groups.push((group, idx));
+                    group += compiled.captures_len();
+                }
+                continue;
+            }
+
+            let forms = if word.inflections {
+                inflections(&word.word)
+            } else {
+                vec![word.word.clone()]
+            };
+
+            for form in forms {
+                if let Some((text, runs)) = fold_pattern(&form, normalization.as_ref()) {
+                    texts.push(text);
+                    patterns.push(Pattern { word: idx, runs });
+                }
+            }
+        }
+
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(texts)?;
+        let exception_automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(exceptions.iter().filter_map(|exception| {
+                fold_pattern(exception, normalization.as_ref()).map(|(text, _)| text)
+            }))?;
+
+        // this only fails if the regexes are too large together
+        let regex = (!alternatives.is_empty())
+            .then(|| {
+                RegexBuilder::new(&alternatives.join("|"))
+                    .case_insensitive(true)
+                    .size_limit(REGEXES_SIZE_LIMIT)
+                    .dfa_size_limit(REGEXES_SIZE_LIMIT)
+                    .nest_limit(REGEX_NEST_LIMIT + 1)
+                    .build()
+                    .ok()
+            })
+            .flatten();
+
+        Ok(Self {
+            words,
+            patterns,
+            automaton,
+            regex,
+            regex_groups,
+            exceptions: exception_automaton,
+            normalization,
+            publishers,
+        })
+    }
+
+    /// returns the occurrences of the words in `content`, skipping the ones that
+    /// are part of another word unless the kind of the word allows it
+    ///
+    /// when normalizing, a collapsed character only matches if it's repeated at
+    /// least as many times as in the word, so that `ass` doesn't match `as`
+    ///
+    /// regexes are matched against the original content instead
+    ///
+    /// occurrences that are inside an exception are skipped
+    pub fn find(&self, content: &str) -> Vec<Match<'_>> {
+        let folded = Folded::new(content, self.normalization.as_ref());
+        let boundaries = word_boundaries(content);
+        let exceptions = self
+            .exceptions
+            .find_overlapping_iter(&folded.text)
+            .filter(|found| !found.is_empty())
+            .map(|found| folded.original(found.range()))
+            .collect::<Vec<_>>();
+        let is_boundary = |idx| boundaries.binary_search(&idx).is_ok();
+        let next_boundary = |idx| {
+            boundaries
+                .get(boundaries.partition_point(|&boundary| boundary < idx))
+                .copied()
+        };
+        let previous_boundary = |idx| {
+            boundaries
+                .get(
+                    boundaries
+                        .partition_point(|&boundary| boundary <= idx)
+                        .checked_sub(1)?,
+                )
+                .copied()
+        };
+
+        self.automaton
+            .find_overlapping_iter(&folded.text)
+            .filter(|found| !found.is_empty())
+            .filter_map(|found| {
+                let pattern = self.patterns.get(found.pattern().as_usize())?;
+                let word = self.words.get(pattern.word)?;
+                let matched = folded.original(found.range());
+
+                let (start_bounded, end_bounded) = match word.kind {
+                    WordKind::Word | WordKind::Phrase => (true, true),
+                    WordKind::Prefix => (true, false),
+                    WordKind::Suffix => (false, true),
+                    WordKind::Substring | WordKind::Regex => (false, false),
+                };
+                if !folded
+                    .runs
+                    .get(found.range())?
+                    .iter()
+                    .zip(&pattern.runs)
+                    .all(|(run, pattern_run)| run >= pattern_run)
+                    || start_bounded && !is_boundary(matched.start)
+                    || end_bounded && !is_boundary(matched.end)
+                {
+                    return None;
+                }
+
+                // the whole word starting or ending with the word is spoilered
+                let span = match word.kind {
+                    WordKind::Prefix => matched.start..next_boundary(matched.end)?,
+                    WordKind::Suffix => previous_boundary(matched.start)?..matched.end,
+                    WordKind::Word | WordKind::Phrase | WordKind::Substring | WordKind::Regex => {
+                        matched
+                    }
+                };
+
+                Some(Match { word, span })
+            })
+            .chain(self.regex.iter().flat_map(|regex| {
+                regex.captures_iter(content).filter_map(|captures| {
+                    let (found, idx) = self
+                        .regex_groups
+                        .iter()
+                        .find_map(|(group, idx)| Some((captures.get(*group)?, *idx)))?;
+
+                    (!found.as_str().is_empty()).then_some(())?;
+                    Some(Match {
+                        word: self.words.get(idx)?,
+                        span: found.range(),
+                    })
+                })
+            }))
+            .filter(|found| {
+                !exceptions.iter().any(|exception| {
+                    exception.start <= found.span.start && found.span.end <= exception.end
+                })
+            })
+            .collect()
+    }
+}
+
+/// compiles a regex word, limiting its size so that it can't slow down
+/// matching
+///
+/// the regex engine matches in linear time so the pattern itself can't cause
+/// catastrophic backtracking
+pub fn regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT)
+        .nest_limit(REGEX_NEST_LIMIT)
+        .build()
+}
+
+/// the filter to use in a channel, cached so that its settings aren't fetched
+/// for every message
+pub struct ChannelFilter {
+    guild_id: Id<GuildMarker>,
+    /// `None` if words aren't put in spoilers in the channel
+    filter: Option<Arc<Filter>>,
+}
+
+/// returns the filter of the guild, building it from the database if it isn't
+/// cached
+pub async fn get(ctx: &Context, guild_id: Id<GuildMarker>) -> Result<Arc<Filter>> {
+    if let Some(filter) = ctx.filters.get(&guild_id) {
+        return Ok(Arc::clone(&filter));
+    }
+
+    let generation = ctx.filters_generation.load(Ordering::SeqCst);
+    let filter = Arc::new(build(ctx, guild_id, None).await?);
+    cache(ctx, &ctx.filters, guild_id, Arc::clone(&filter), generation);
+
+    Ok(filter)
+}
+
+/// returns the filter to use in the channel, or `None` if words aren't put in
+/// spoilers in it
+///
+/// threads and forum posts use the settings of their parent, and channels
+/// without settings use the settings of their category, or the guild's filter
+/// if it doesn't have any either
+pub async fn get_in_channel(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+) -> Result<Option<Arc<Filter>>> {
+    let (parent_id, _) = channel_pair(
+        &*ctx
+            .cache
+            .channel(channel_id)
+            .context("channel is not cached")?,
+    )?;
+    if let Some(cached) = ctx.channel_filters.get(&parent_id) {
+        return Ok(cached.filter.clone());
+    }
+
+    let generation = ctx.filters_generation.load(Ordering::SeqCst);
+    let category_id = ctx
+        .cache
+        .channel(parent_id)
+        .and_then(|parent| parent.parent_id);
+    let candidate_ids: Vec<_> = [Some(parent_id), category_id]
+        .into_iter()
+        .flatten()
+        .collect();
+    let settings = database::channel_settings(&ctx.db, &candidate_ids).await?;
+
+    let filter = match closest_settings(&candidate_ids, &settings) {
+        None => Some(get(ctx, guild_id).await?),
+        Some((_, false)) => None,
+        Some((closest_id, true)) => Some(Arc::new(build(ctx, guild_id, Some(closest_id)).await?)),
+    };
+    cache(
+        ctx,
+        &ctx.channel_filters,
+        parent_id,
+        ChannelFilter {
+            guild_id,
+            filter: filter.clone(),
+        },
+        generation,
+    );
+
+    Ok(filter)
+}
+
+/// returns the closest of the candidates with settings, whose packs and words
+/// are used, and whether words are put in spoilers there, or `None` if none of
+/// them have settings
+///
+/// the candidates are ordered from the closest, and whether words are put in
+/// spoilers comes from the closest one that set it, so adding words to a
+/// channel doesn't enable it in a disabled category
+fn closest_settings(
+    candidate_ids: &[Id<ChannelMarker>],
+    settings: &[ChannelSettings],
+) -> Option<(Id<ChannelMarker>, bool)> {
+    let mut candidates = candidate_ids.iter().filter_map(|candidate_id| {
+        settings
+            .iter()
+            .find(|channel| channel.channel_id == *candidate_id)
+    });
+    let closest = candidates.next()?;
+    let enabled = closest
+        .enabled
+        .or_else(|| candidates.find_map(|channel| channel.enabled))
+        .unwrap_or(true);
+
+    Some((closest.channel_id, enabled))
+}
+
+/// caches the filter unless the filters were invalidated since `generation`
+/// was read before building it, since it might be built from outdated words
+///
+/// the generation is checked while holding the lock of the entry so that an
+/// invalidation can't remove the entries before the filter is inserted
+fn cache<K: Eq + Hash, V>(
+    ctx: &Context,
+    filters: &DashMap<K, V>,
+    key: K,
+    filter: V,
+    generation: u64,
+) {
+    let entry = filters.entry(key);
+    if ctx.filters_generation.load(Ordering::SeqCst) != generation {
+        return;
+    }
+
+    match entry {
+        Entry::Occupied(mut occupied) => {
+            occupied.insert(filter);
+        }
+        Entry::Vacant(vacant) => {
+            vacant.insert(filter);
+        }
+    }
+}
+
+/// builds the filter of the guild from the database, with the overrides of the
+/// channel settings of `channel_id` if it's given
+async fn build(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel_id: Option<Id<ChannelMarker>>,
+) -> Result<Filter> {
+    let settings = database::settings(&ctx.db, guild_id).await?;
+    let normalization = if settings.normalize {
+        Some(Normalization::new(&settings.leet_map)?)
+    } else {
+        None
+    };
+
+    Filter::new(
+        database::words_in(&ctx.db, guild_id, channel_id).await?,
+        &database::exceptions(&ctx.db, guild_id).await?,
+        normalization,
+        database::subscriptions(&ctx.db, guild_id)
+            .await?
+            .into_iter()
+            .map(|subscription| subscription.publisher_id)
+            .collect(),
+    )
+}
+
+/// removes the cached filters of the guild and of the guilds subscribed to its
+/// list, should be called when its words, allowed words, exceptions,
+/// normalization settings, subscriptions or channel settings change
+pub fn invalidate(ctx: &Context, guild_id: Id<GuildMarker>) {
+    ctx.filters_generation.fetch_add(1, Ordering::SeqCst);
+    ctx.filters
+        .retain(|id, filter| *id != guild_id && !filter.publishers.contains(&guild_id));
+    ctx.channel_filters.retain(|_, channel| {
+        channel.guild_id != guild_id
+            && !channel
+                .filter
+                .as_ref()
+                .is_some_and(|filter| filter.publishers.contains(&guild_id))
+    });
+}
+
+/// removes the cached filter of the channel, should be called when it's moved
+/// to another category
+pub fn invalidate_channel(ctx: &Context, channel_id: Id<ChannelMarker>) {
+    ctx.filters_generation.fetch_add(1, Ordering::SeqCst);
+    ctx.channel_filters.remove(&channel_id);
+}
+
+/// removes the cached filters of all guilds, should be called when the default
+/// words change
+pub fn invalidate_all(ctx: &Context) {
+    ctx.filters_generation.fetch_add(1, Ordering::SeqCst);
+    ctx.filters.clear();
+    ctx.channel_filters.clear();
+}
+
+/// returns the byte indices of `content` that are on a unicode word boundary,
+/// sorted and including the start and the end of `content`
+fn word_boundaries(content: &str) -> Vec<usize> {
+    content
+        .split_word_bound_indices()
+        .map(|(idx, _)| idx)
+        .chain([content.len()])
+        .collect()
+}
+
+/// folds a word or an exception the way messages are folded, returning the
+/// folded text without the spaces around it and its runs, or `None` if it's
+/// empty
+#[allow(clippy::integer_arithmetic, clippy::string_slice)]
+fn fold_pattern(text: &str, normalization: Option<&Normalization>) -> Option<(String, Vec<usize>)> {
+    let folded = Folded::new(text, normalization);
+    let start = folded.text.len() - folded.text.trim_start().len();
+    let end = folded.text.trim_end().len();
+    if start >= end {
+        return None;
+    }
+
+    Some((
+        folded.text[start..end].to_owned(),
+        folded.runs.get(start..end)?.to_vec(),
+    ))
+}
+
+/// returns the word with its plural, -ing and -ed forms
+fn inflections(word: &str) -> Vec<String> {
+    let mut forms = vec![
+        word.to_owned(),
+        format!("{word}s"),
+        format!("{word}es"),
+        format!("{word}ing"),
+        format!("{word}ed"),
+    ];
+
+    if let Some(stem) = word.strip_suffix("ie") {
+        forms.push(format!("{stem}ying"));
+    }
+    if let Some(stem) = word.strip_suffix('e') {
+        forms.push(format!("{stem}ing"));
+        forms.push(format!("{word}d"));
+    }
+    if let Some(stem) = word.strip_suffix('y') {
+        forms.push(format!("{stem}ies"));
+        forms.push(format!("{stem}ied"));
+    }
+
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u');
+    let mut chars = word.chars().rev();
+    if let (Some(last), Some(before_last)) = (chars.next(), chars.next()) {
+        if last.is_alphabetic()
+            && !is_vowel(last)
+            && !matches!(last, 'w' | 'x' | 'y')
+            && is_vowel(before_last)
+        {
+            forms.push(format!("{word}{last}ing"));
+            forms.push(format!("{word}{last}ed"));
+        }
+    }
+
+    forms
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::id::Id;
+
+    use super::{closest_settings, inflections, Filter};
+    use crate::database::{ChannelSettings, Word, WordKind};
+
+    /// builds a filter of the given words and whether to match their
+    /// inflections
+    #[allow(clippy::expect_used)]
+    fn filter(entries: &[(&str, bool)], exceptions: &[String]) -> Filter {
+        let words = entries
+            .iter()
+            .map(|(entry, inflections)| {
+                let (word, kind) = WordKind::parse(entry);
+                Word {
+                    id: 0,
+                    word,
+                    kind,
+                    inflections: *inflections,
+                    origin: None,
+                }
+            })
+            .collect();
+
+        Filter::new(words, exceptions, None, vec![]).expect("the words are valid")
+    }
+
+    /// returns the parts of `content` the filter found, sorted by where they
+    /// start
+    #[allow(clippy::indexing_slicing, clippy::string_slice)]
+    fn found<'content>(filter: &Filter, content: &'content str) -> Vec<&'content str> {
+        let mut spans = filter
+            .find(content)
+            .into_iter()
+            .map(|found| found.span)
+            .collect::<Vec<_>>();
+        spans.sort_unstable_by_key(|span| (span.start, span.end));
+
+        spans.into_iter().map(|span| &content[span]).collect()
+    }
+
+    #[test]
+    fn words_only_match_on_their_own() {
+        let filter = filter(&[("kill", false)], &[]);
+
+        assert_eq!(found(&filter, "KILL it, not the skill"), ["KILL"]);
+    }
+
+    #[test]
+    fn prefixes_match_the_whole_word() {
+        let filter = filter(&[("suicid*", false)], &[]);
+
+        assert_eq!(
+            found(&filter, "suicidal thoughts, not nonsuicidal"),
+            ["suicidal"]
+        );
+    }
+
+    #[test]
+    fn suffixes_match_the_whole_word() {
+        let filter = filter(&[("*cide", false)], &[]);
+
+        assert_eq!(found(&filter, "genocide, not cider"), ["genocide"]);
+    }
+
+    #[test]
+    fn substrings_match_inside_words() {
+        let filter = filter(&[("*word*", false)], &[]);
+
+        assert_eq!(found(&filter, "swordfish and words"), ["word", "word"]);
+    }
+
+    #[test]
+    fn phrases_match_with_any_spacing() {
+        let filter = filter(&[("kill yourself", false)], &[]);
+
+        assert_eq!(
+            found(&filter, "kill... yourself or kill  yourself"),
+            ["kill... yourself", "kill  yourself"]
+        );
+    }
+
+    #[test]
+    fn regexes_match_the_original_content() {
+        let filter = filter(&[("/k[i1]ll/", false)], &[]);
+
+        assert_eq!(found(&filter, "K1LL and kill"), ["K1LL", "kill"]);
+    }
+
+    #[test]
+    fn regexes_match_together() {
+        let filter = filter(&[("/(k)[i1](ll)/", false), ("/d(ie|ed)/", false)], &[]);
+
+        assert_eq!(
+            found(&filter, "kill and die or k1ll"),
+            ["kill", "die", "k1ll"]
+        );
+    }
+
+    #[test]
+    fn inflections_match() {
+        let filter = filter(&[("kill", true)], &[]);
+
+        assert_eq!(
+            found(&filter, "kills killing killed killer"),
+            ["kills", "killing", "killed"]
+        );
+    }
+
+    #[test]
+    fn exceptions_are_skipped() {
+        let filter = filter(&[("*kill*", false)], &["skill issue".to_owned()]);
+
+        assert_eq!(found(&filter, "skill issue, skills"), ["kill"]);
+    }
+
+    #[test]
+    fn inflected_forms() {
+        for (word, expected) in [
+            ("kill", ["kills", "killing", "killed"]),
+            ("die", ["dies", "dying", "died"]),
+            ("cry", ["cries", "crying", "cried"]),
+        ] {
+            let forms = inflections(word);
+
+            assert!(forms.iter().any(|form| form == word));
+            for form in expected {
+                assert!(forms.iter().any(|inflected| inflected == form), "{form}");
+            }
+        }
+    }
+
+    #[test]
+    fn channels_in_disabled_categories_stay_disabled() {
+        let channel_id = Id::new(1);
+        let category_id = Id::new(2);
+        let candidate_ids = [channel_id, category_id];
+
+        // the category was disabled, then a word was added to the channel
+        let word_added = [
+            ChannelSettings {
+                channel_id: category_id,
+                enabled: Some(false),
+            },
+            ChannelSettings {
+                channel_id,
+                enabled: None,
+            },
+        ];
+        assert_eq!(
+            closest_settings(&candidate_ids, &word_added),
+            Some((channel_id, false))
+        );
+
+        // the channel was then enabled on its own
+        let channel_enabled = [
+            ChannelSettings {
+                channel_id: category_id,
+                enabled: Some(false),
+            },
+            ChannelSettings {
+                channel_id,
+                enabled: Some(true),
+            },
+        ];
+        assert_eq!(
+            closest_settings(&candidate_ids, &channel_enabled),
+            Some((channel_id, true))
+        );
+
+        let without_category = [ChannelSettings {
+            channel_id,
+            enabled: None,
+        }];
+        assert_eq!(
+            closest_settings(&candidate_ids, &without_category),
+            Some((channel_id, true))
+        );
+        assert_eq!(closest_settings(&candidate_ids, &[]), None);
+    }
+}
@@ -0,0 +1,319 @@
+use std::{collections::HashMap, iter, ops::Range};
+
+use anyhow::{bail, Result};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_security::confusable_detection::skeleton;
+
+/// the characters that are replaced before matching words to catch words
+/// obfuscated with fullwidth letters, accents, look-alikes or leetspeak
+pub struct Normalization {
+    /// the replacement of each character in the guild's leet map
+    leet_map: HashMap<char, char>,
+}
+
+impl Normalization {
+    /// parses a leet map made of whitespace separated pairs of a character and
+    /// its replacement, such as `4a 3e`
+    pub fn new(leet_map: &str) -> Result<Self> {
+        let mut replacements = HashMap::new();
+        for pair in leet_map.split_whitespace() {
+            let mut chars = pair.chars();
+            let (Some(c), Some(replacement), None) = (chars.next(), chars.next(), chars.next())
+            else {
+                bail!("`{pair}` in the leet map isn't a character followed by its replacement");
+            };
+            replacements.insert(c, replacement);
+        }
+
+        Ok(Self {
+            leet_map: replacements,
+        })
+    }
+
+    /// appends the normalized form of `c` to `normalized`
+    ///
+    /// this decomposes it, removes its accents, lowercases it, replaces it
+    /// using the leet map and replaces it with the character it's confusable
+    /// with
+    fn push(&self, c: char, normalized: &mut String) {
+        for decomposed in iter::once(c)
+            .nfkd()
+            .filter(|decomposed| !is_combining_mark(*decomposed))
+        {
+            for lowercase in decomposed.to_lowercase() {
+                let replaced = self.leet_map.get(&lowercase).copied().unwrap_or(lowercase);
+                normalized.extend(
+                    skeleton(replaced.encode_utf8(&mut [0; 4]))
+                        .filter(|confusable| !is_combining_mark(*confusable))
+                        .flat_map(char::to_lowercase),
+                );
+            }
+        }
+    }
+}
+
+/// the lowercased content of a message, used to match words case
+/// insensitively while keeping the original content's casing
+///
+/// spaces and punctuation are collapsed into a single space so that phrases
+/// match with any spacing or punctuation between their words
+///
+/// when normalizing, the content is also normalized, invisible characters and
+/// the spaces between spaced out letters are removed and repeated characters
+/// are collapsed into one
+pub struct Folded {
+    pub text: String,
+    /// the start of the original character of each byte in `text`
+    pub starts: Vec<usize>,
+    /// the end of the original character of each byte in `text`
+    pub ends: Vec<usize>,
+    /// the number of times the character of each byte in `text` was repeated
+    /// before being collapsed
+    pub runs: Vec<usize>,
+}
+
+impl Folded {
+    /// folds the content, normalizing it if `normalize` is given
+    pub fn new(content: &str, normalize: Option<&Normalization>) -> Self {
+        normalize.map_or_else(
+            || Self::lowercased(content),
+            |normalization| Self::normalized(content, normalization),
+        )
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            text: String::with_capacity(capacity),
+            starts: Vec::with_capacity(capacity),
+            ends: Vec::with_capacity(capacity),
+            runs: Vec::with_capacity(capacity),
+        }
+    }
+
+    #[allow(clippy::integer_arithmetic)]
+    fn lowercased(content: &str) -> Self {
+        let mut folded = Self::with_capacity(content.len());
+
+        for (idx, c) in content.char_indices() {
+            for lowercase in c.to_lowercase() {
+                folded.push(lowercase, idx, idx + c.len_utf8(), false);
+            }
+        }
+
+        folded
+    }
+
+    #[allow(clippy::integer_arithmetic)]
+    fn normalized(content: &str, normalization: &Normalization) -> Self {
+        let mut folded = Self::with_capacity(content.len());
+        let spacing = letter_spacing(content);
+        let mut normalized = String::new();
+
+        for (idx, c) in content.char_indices() {
+            if is_invisible(c) || spacing.binary_search(&idx).is_ok() {
+                continue;
+            }
+
+            normalized.clear();
+            normalization.push(c, &mut normalized);
+            for normalized_c in normalized.chars() {
+                folded.push(normalized_c, idx, idx + c.len_utf8(), true);
+            }
+        }
+
+        folded
+    }
+
+    /// pushes `c` that comes from the original character spanning from `start`
+    /// to `end`, or adds it to the run of the last character if it's the same
+    /// and it's a separator or `collapse` is set
+    #[allow(clippy::integer_arithmetic, clippy::indexing_slicing)]
+    fn push(&mut self, c: char, start: usize, end: usize, collapse: bool) {
+        let separator = is_separator(c);
+        let pushed = if separator { ' ' } else { c };
+
+        if (collapse || separator) && self.text.ends_with(pushed) {
+            let last = self.text.len() - pushed.len_utf8();
+            self.ends[last..].fill(end);
+            for run in &mut self.runs[last..] {
+                *run += 1;
+            }
+        } else {
+            self.text.push(pushed);
+            self.starts.resize(self.text.len(), start);
+            self.ends.resize(self.text.len(), end);
+            self.runs.resize(self.text.len(), 1);
+        }
+    }
+
+    /// maps the given non-empty span in `text` to its span in the original
+    /// content
+    #[allow(clippy::integer_arithmetic, clippy::indexing_slicing)]
+    pub fn original(&self, span: Range<usize>) -> Range<usize> {
+        self.starts[span.start]..self.ends[span.end - 1]
+    }
+}
+
+/// returns the sorted indices of the separators between letters that are
+/// spaced out, such as the spaces in `k i l l`
+#[allow(clippy::integer_arithmetic, clippy::indexing_slicing)]
+fn letter_spacing(content: &str) -> Vec<usize> {
+    let chars = content.char_indices().collect::<Vec<_>>();
+    let is_letter = |idx: usize| chars.get(idx).is_some_and(|(_, c)| c.is_alphanumeric());
+    let is_spacing = |idx: usize| {
+        chars
+            .get(idx)
+            .is_some_and(|(_, c)| c.is_whitespace() || matches!(c, '.' | '-' | '_' | '*'))
+    };
+
+    let mut separators = vec![];
+    let mut idx = 0;
+    while idx < chars.len() {
+        if !is_letter(idx) || idx > 0 && is_letter(idx - 1) || is_letter(idx + 1) {
+            idx += 1;
+            continue;
+        }
+
+        let mut spaced = vec![];
+        let mut end = idx;
+        while is_spacing(end + 1) && is_letter(end + 2) && !is_letter(end + 3) {
+            spaced.push(chars[end + 1].0);
+            end += 2;
+        }
+        if spaced.len() >= 2 {
+            separators.append(&mut spaced);
+        }
+
+        idx = end + 1;
+    }
+
+    separators
+}
+
+/// whether `c` is a space or a punctuation character
+const fn is_separator(c: char) -> bool {
+    c.is_whitespace()
+        || c.is_ascii_punctuation()
+        || matches!(c, '\u{2010}'..='\u{2027}' | '\u{3000}'..='\u{3003}')
+}
+
+/// whether `c` is a character that isn't rendered, such as zero width spaces
+/// and joiners
+const fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{ad}'
+            | '\u{34f}'
+            | '\u{61c}'
+            | '\u{115f}'..='\u{1160}'
+            | '\u{17b4}'..='\u{17b5}'
+            | '\u{180b}'..='\u{180f}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2060}'..='\u{206f}'
+            | '\u{3164}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{feff}'
+            | '\u{ffa0}'
+            | '\u{1d173}'..='\u{1d17a}'
+            | '\u{e0000}'..='\u{e0fff}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{letter_spacing, Folded, Normalization};
+
+    /// returns the part of `content` that `word` comes from once they're both
+    /// folded, or `None` if the folded content doesn't have it
+    #[allow(
+        clippy::integer_arithmetic,
+        clippy::indexing_slicing,
+        clippy::string_slice
+    )]
+    fn original<'content>(
+        content: &'content str,
+        normalization: Option<&Normalization>,
+        word: &str,
+    ) -> Option<&'content str> {
+        let folded = Folded::new(content, normalization);
+        let folded_word = Folded::new(word, normalization).text;
+        let start = folded.text.find(&folded_word)?;
+
+        Some(&content[folded.original(start..start + folded_word.len())])
+    }
+
+    #[allow(clippy::expect_used)]
+    fn normalization() -> Normalization {
+        Normalization::new("1i 3e").expect("the leet map is valid")
+    }
+
+    #[test]
+    fn lowercased() {
+        assert_eq!(original("I'd KILL it", None, "kill"), Some("KILL"));
+        assert_eq!(original("K\u{130}LL", None, "ki"), Some("K\u{130}"));
+        assert_eq!(original("k1ll", None, "kill"), None);
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(
+            original("kill... yourself", None, "kill yourself"),
+            Some("kill... yourself")
+        );
+    }
+
+    #[test]
+    fn multibyte() {
+        assert_eq!(
+            original("a k\u{ed}ll b", Some(&normalization()), "kill"),
+            Some("k\u{ed}ll")
+        );
+    }
+
+    #[test]
+    fn fullwidth() {
+        let content = "\u{ff2b}\u{ff49}\u{ff4c}\u{ff4c} it";
+
+        assert_eq!(
+            original(content, Some(&normalization()), "kill"),
+            Some("\u{ff2b}\u{ff49}\u{ff4c}\u{ff4c}")
+        );
+    }
+
+    #[test]
+    fn leetspeak_and_invisible_characters() {
+        assert_eq!(
+            original("k1\u{200b}ll", Some(&normalization()), "kill"),
+            Some("k1\u{200b}ll")
+        );
+    }
+
+    #[test]
+    fn repeated_letters() {
+        let folded = Folded::new("kiiill", Some(&normalization()));
+
+        assert_eq!(folded.text, "kil");
+        assert_eq!(folded.runs, [1, 3, 2]);
+        assert_eq!(folded.original(0..3), 0..6);
+    }
+
+    #[test]
+    fn spaced_out() {
+        assert_eq!(
+            original("i'll k i l l you", Some(&normalization()), "kill"),
+            Some("k i l l")
+        );
+        assert_eq!(
+            original("k.i.l.l", Some(&normalization()), "kill"),
+            Some("k.i.l.l")
+        );
+    }
+
+    #[test]
+    fn letter_spacing_only_removes_spaced_out_letters() {
+        assert_eq!(letter_spacing("k i l l"), [1, 3, 5]);
+        assert!(letter_spacing("i am a cat").is_empty());
+        assert!(letter_spacing("a b").is_empty());
+    }
+}
@@ -4,11 +4,14 @@ use anyhow::{bail, Result};
 use twilight_http::Client;
 use twilight_interactions::command::CreateCommand;
 use twilight_model::{
-    application::interaction::{ApplicationCommand, Interaction, MessageComponentInteraction},
+    application::interaction::{
+        ApplicationCommand, ApplicationCommandAutocomplete, Interaction,
+        MessageComponentInteraction,
+    },
     channel::message::MessageFlags,
-    http::interaction::{InteractionResponse, InteractionResponseType},
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
     id::{
-        marker::{ApplicationMarker, GuildMarker},
+        marker::{ApplicationMarker, GuildMarker, InteractionMarker, UserMarker},
         Id,
     },
 };
@@ -16,7 +19,9 @@ use twilight_util::builder::InteractionResponseDataBuilder;
 
 use crate::{
     interaction::{
-        add_custom_word::AddCustomWord, add_default_word::AddDefaultWord, tag::Tag, tw::Tw,
+        add_custom_word::AddCustomWord, add_default_word::AddDefaultWord, bypass::Bypass,
+        channels::Channels, default_words::DefaultWords, exceptions::Exceptions, opt_out::OptOut,
+        packs::Packs, settings::Settings, share::Share, tag::Tag, tw::Tw, words::Words,
     },
     Context,
 };
@@ -24,16 +29,32 @@ use crate::{
 mod add_custom_word;
 mod add_default_word;
 mod allow;
+mod bypass;
+mod channels;
+mod default_words;
+mod exceptions;
+mod opt_out;
+mod packs;
+mod settings;
+mod share;
+mod suggestion;
 mod tag;
 mod tw;
+mod validate;
+mod words;
 
-const TEST_GUILD_ID: Id<GuildMarker> = Id::new(903_367_565_349_384_202);
+/// the maximum number of characters in a list, leaving room for the number of
+/// items that didn't fit
+const MAX_LIST_LEN: usize = 1950;
 
 #[allow(clippy::wildcard_enum_match_arm)]
 pub async fn handle(ctx: Context, interaction: Interaction) -> Result<()> {
     match interaction {
         Interaction::ApplicationCommand(cmd) => handle_command(&ctx, *cmd).await,
         Interaction::MessageComponent(component) => handle_component(&ctx, *component).await,
+        Interaction::ApplicationCommandAutocomplete(autocomplete) => {
+            handle_autocomplete(&ctx, *autocomplete).await
+        }
         _ => bail!("unknown interaction: {interaction:#?}"),
     }
 }
@@ -42,11 +63,27 @@ async fn handle_command(ctx: &Context, mut command: ApplicationCommand) -> Resul
     let command_id = command.id;
     let token = mem::take(&mut command.token);
 
+    if words::is_import(&command) {
+        return handle_deferred_command(ctx, command_id, &token, command).await;
+    }
+
     let reply = match command.data.name.as_str() {
-        "tw" => tw::run(ctx, command).await?,
-        "tag" => tag::run(ctx, command).await?,
-        "add_custom_word" => add_custom_word::run(ctx, command).await?,
-        "add_default_word" => add_default_word::run(ctx, command.data).await?,
+        "add_default_word" | "default_words" if !is_owner(ctx, command.author_id()) => {
+            text("only my owners can use this")
+        }
+        "tw" => text(tw::run(ctx, command).await?),
+        "tag" => text(tag::run(ctx, command).await?),
+        "add_custom_word" => text(add_custom_word::run(ctx, command).await?),
+        "settings" => text(settings::run(ctx, command).await?),
+        "words" => words::run(ctx, command).await?,
+        "exceptions" => text(exceptions::run(ctx, command).await?),
+        "packs" => text(packs::run(ctx, command).await?),
+        "share" => text(share::run(ctx, command).await?),
+        "channels" => text(channels::run(ctx, command).await?),
+        "bypass" => text(bypass::run(ctx, command).await?),
+        "opt_out" => text(opt_out::run(ctx, command).await?),
+        "add_default_word" => text(add_default_word::run(ctx, command.data).await?),
+        "default_words" => text(default_words::run(ctx, command.data).await?),
         _ => bail!("unknown command: {command:#?}"),
     };
 
@@ -57,9 +94,33 @@ async fn handle_command(ctx: &Context, mut command: ApplicationCommand) -> Resul
             &token,
             &InteractionResponse {
                 kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(reply),
+            },
+        )
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// responds to the command later, for commands that can take longer than the
+/// interaction deadline
+async fn handle_deferred_command(
+    ctx: &Context,
+    command_id: Id<InteractionMarker>,
+    token: &str,
+    command: ApplicationCommand,
+) -> Result<()> {
+    let client = ctx.http.interaction(ctx.application_id);
+
+    client
+        .create_response(
+            command_id,
+            token,
+            &InteractionResponse {
+                kind: InteractionResponseType::DeferredChannelMessageWithSource,
                 data: Some(
                     InteractionResponseDataBuilder::new()
-                        .content(reply.to_owned())
                         .flags(MessageFlags::EPHEMERAL)
                         .build(),
                 ),
@@ -68,6 +129,26 @@ async fn handle_command(ctx: &Context, mut command: ApplicationCommand) -> Resul
         .exec()
         .await?;
 
+    // the response has to be edited even if the command fails, otherwise it
+    // stays as "thinking..."
+    let reply = match words::run(ctx, command).await {
+        Ok(reply) => reply,
+        Err(err) => {
+            client
+                .update_response(token)
+                .content(Some("something went wrong, please try again"))?
+                .exec()
+                .await?;
+            return Err(err);
+        }
+    };
+
+    client
+        .update_response(token)
+        .content(reply.content.as_deref())?
+        .exec()
+        .await?;
+
     Ok(())
 }
 
@@ -75,7 +156,21 @@ async fn handle_component(ctx: &Context, mut component: MessageComponentInteract
     let component_id = component.id;
     let token = mem::take(&mut component.token);
 
-    let response = allow::run(ctx, component).await?;
+    let custom_id = &component.data.custom_id;
+    let response = if custom_id.starts_with(words::PAGE_CUSTOM_ID_PREFIX) {
+        words::page(ctx, component).await?
+    } else if custom_id.starts_with(suggestion::CUSTOM_ID_PREFIX) {
+        if is_owner(ctx, component.author_id()) {
+            suggestion::review(ctx, component).await?
+        } else {
+            InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(text("only my owners can use this")),
+            }
+        }
+    } else {
+        allow::run(ctx, component).await?
+    };
 
     ctx.http
         .interaction(ctx.application_id)
@@ -86,7 +181,90 @@ async fn handle_component(ctx: &Context, mut component: MessageComponentInteract
     Ok(())
 }
 
-pub async fn create(http: &Client, application_id: Id<ApplicationMarker>) -> Result<()> {
+async fn handle_autocomplete(
+    ctx: &Context,
+    mut autocomplete: ApplicationCommandAutocomplete,
+) -> Result<()> {
+    let autocomplete_id = autocomplete.id;
+    let token = mem::take(&mut autocomplete.token);
+
+    let choices = match autocomplete.data.name.as_str() {
+        "default_words" if !is_owner(ctx, autocomplete.author_id()) => vec![],
+        "words" => words::autocomplete(ctx, autocomplete).await?,
+        "exceptions" => exceptions::autocomplete(ctx, autocomplete).await?,
+        "packs" => packs::autocomplete(ctx, autocomplete).await?,
+        "share" => share::autocomplete(ctx, autocomplete).await?,
+        "channels" => channels::autocomplete(ctx, autocomplete).await?,
+        "default_words" => default_words::autocomplete(ctx, autocomplete).await?,
+        _ => bail!("unknown autocomplete command: {autocomplete:#?}"),
+    };
+
+    ctx.http
+        .interaction(ctx.application_id)
+        .create_response(
+            autocomplete_id,
+            &token,
+            &InteractionResponse {
+                kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .choices(choices)
+                        .build(),
+                ),
+            },
+        )
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// returns whether the user can use the owner commands
+fn is_owner(ctx: &Context, user_id: Option<Id<UserMarker>>) -> bool {
+    user_id.is_some_and(|id| ctx.owner_ids.contains(&id))
+}
+
+/// returns an ephemeral reply with the given content
+fn text(content: impl Into<String>) -> InteractionResponseData {
+    InteractionResponseDataBuilder::new()
+        .content(content.into())
+        .flags(MessageFlags::EPHEMERAL)
+        .build()
+}
+
+/// wraps the text in a code span so that markdown in it isn't rendered
+fn code(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {text} ``")
+    } else {
+        format!("`{text}`")
+    }
+}
+
+/// appends the items to `content` as a list while they fit in a message, then
+/// how many items didn't fit
+#[allow(clippy::integer_arithmetic)]
+fn push_list(content: &mut String, items: Vec<String>) {
+    let count = items.len();
+    for (idx, item) in items.into_iter().enumerate() {
+        if content.len() + item.len() + 3 > MAX_LIST_LEN {
+            content.push_str("- and ");
+            content.push_str(&(count - idx).to_string());
+            content.push_str(" more\n");
+            return;
+        }
+        content.push_str("- ");
+        content.push_str(&item);
+        content.push('\n');
+    }
+}
+
+/// sets the global commands and the owner commands in the owner guilds
+pub async fn create(
+    http: &Client,
+    application_id: Id<ApplicationMarker>,
+    owner_guild_ids: &[Id<GuildMarker>],
+) -> Result<()> {
     let client = http.interaction(application_id);
 
     client
@@ -94,16 +272,32 @@ pub async fn create(http: &Client, application_id: Id<ApplicationMarker>) -> Res
             Tw::create_command().into(),
             Tag::create_command().into(),
             AddCustomWord::create_command().into(),
+            Settings::create_command().into(),
+            Words::create_command().into(),
+            Exceptions::create_command().into(),
+            Packs::create_command().into(),
+            Share::create_command().into(),
+            Channels::create_command().into(),
+            Bypass::create_command().into(),
+            OptOut::create_command().into(),
         ])
         .exec()
         .await?
         .model()
         .await?;
 
-    client
-        .set_guild_commands(TEST_GUILD_ID, &[AddDefaultWord::create_command().into()])
-        .exec()
-        .await?;
+    for guild_id in owner_guild_ids {
+        client
+            .set_guild_commands(
+                *guild_id,
+                &[
+                    AddDefaultWord::create_command().into(),
+                    DefaultWords::create_command().into(),
+                ],
+            )
+            .exec()
+            .await?;
+    }
 
     Ok(())
 }
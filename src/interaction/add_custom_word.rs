@@ -2,60 +2,106 @@ use anyhow::{Context as _, Result};
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_model::{application::interaction::ApplicationCommand, guild::Permissions};
 
-use crate::{database, Context};
+use crate::{
+    database::{self, Suggestion},
+    filter,
+    interaction::{suggestion, validate},
+    Context,
+};
 
 #[derive(CommandModel, CreateCommand)]
 #[command(name = "add_custom_word", desc = "add your own word to censor")]
 pub struct AddCustomWord {
-    #[command(name = "word", desc = "the word to add")]
+    #[command(
+        name = "word",
+        desc = "the word to add, add * to its end or start to also censor words starting or ending with it"
+    )]
     word: String,
     #[command(
         name = "suggest",
         desc = "set true to tell my developer to add this word to the default list"
     )]
     suggest: bool,
+    #[command(
+        name = "match_inside_words",
+        desc = "set true to also censor this word when it's part of another word"
+    )]
+    match_inside_words: Option<bool>,
+    #[command(
+        name = "inflections",
+        desc = "set true to also censor the plural, -ing and -ed forms of this word"
+    )]
+    inflections: Option<bool>,
+    #[command(
+        name = "regex",
+        desc = "set true to add the word as a regex like `\\d+ ?kg`, you can also write it as `/\\d+ ?kg/`"
+    )]
+    regex: Option<bool>,
 }
 
-pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<&'static str> {
+pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<String> {
     let member = command.member.context("command doesn't have a member")?;
     if !member
         .permissions
         .context("member doesn't have permissions attached")?
         .contains(Permissions::MANAGE_GUILD)
     {
-        return Ok("you need the manage guild permission to use this");
+        return Ok("you need the manage guild permission to use this".to_owned());
     }
     let guild_id = command
         .guild_id
         .context("command doesn't have a guild id")?;
 
     let options = AddCustomWord::from_interaction(command.data.into())?;
-    let word = options.word.to_lowercase();
+    let (word, kind) = match validate::word(
+        &options.word,
+        options.regex == Some(true),
+        options.match_inside_words == Some(true),
+    ) {
+        Ok(parsed) => parsed,
+        Err(reason) => return Ok(reason),
+    };
 
-    if database::words(&ctx.db, guild_id)
-        .await?
+    let words = database::words(&ctx.db, guild_id).await?;
+    // a guild can't add a word twice, even with another kind
+    if words
         .iter()
-        .any(|w| w.word == word)
+        .any(|w| w.word == word && (w.kind == kind || w.origin.is_none()))
     {
-        return Ok("this word is already added!");
+        return Ok("this word is already added!".to_owned());
+    }
+    if let Err(reason) = validate::quota(
+        database::custom_word_count(&ctx.db, guild_id).await?,
+        database::regex_word_count(&ctx.db, guild_id).await?,
+        kind,
+    ) {
+        return Ok(reason);
     }
 
+    let inflections = options.inflections.unwrap_or(false);
     if options.suggest {
         let user = member
             .user
             .context("command member doesn't have a user attached")?;
 
-        ctx.http
-            .create_message(ctx.owner_channel_id)
-            .content(&format!(
-                "{}#{} suggested a word: {}",
-                user.name, user.discriminator, word
-            ))?
-            .exec()
-            .await?;
+        suggestion::suggest(
+            ctx,
+            guild_id,
+            command.channel_id,
+            &user,
+            &Suggestion {
+                word: word.clone(),
+                kind,
+                inflections,
+            },
+        )
+        .await?;
     }
 
-    database::add_custom_word(&ctx.db, guild_id, word).await?;
+    if !database::add_custom_word(&ctx.db, guild_id, word, kind, inflections).await? {
+        return Ok("this word is already added!".to_owned());
+    }
+    filter::invalidate(ctx, guild_id);
 
-    Ok("done!")
+    Ok("done!".to_owned())
 }
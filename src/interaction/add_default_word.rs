@@ -2,23 +2,56 @@ use anyhow::Result;
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_model::application::interaction::application_command::CommandData;
 
-use crate::{database, Context};
+use crate::{database, filter, interaction::validate, Context};
 
 #[derive(CommandModel, CreateCommand)]
-#[command(
-    name = "add_default_word",
-    desc = "add a word to censor",
-    default_permission = false
-)]
+#[command(name = "add_default_word", desc = "add a word to censor")]
 pub struct AddDefaultWord {
-    #[command(name = "word", desc = "the new word")]
+    #[command(
+        name = "word",
+        desc = "the new word, add * to its end or start to also censor words starting or ending with it"
+    )]
     word: String,
+    #[command(
+        name = "pack",
+        desc = "the pack to add the word to, it's created if it doesn't exist"
+    )]
+    pack: String,
+    #[command(
+        name = "match_inside_words",
+        desc = "set true to also censor this word when it's part of another word"
+    )]
+    match_inside_words: Option<bool>,
+    #[command(
+        name = "inflections",
+        desc = "set true to also censor the plural, -ing and -ed forms of this word"
+    )]
+    inflections: Option<bool>,
 }
 
-pub async fn run(ctx: &Context, data: CommandData) -> Result<&'static str> {
+pub async fn run(ctx: &Context, data: CommandData) -> Result<String> {
     let options = AddDefaultWord::from_interaction(data.into())?;
+    let (word, kind) = match validate::word(
+        &options.word,
+        false,
+        options.match_inside_words == Some(true),
+    ) {
+        Ok(parsed) => parsed,
+        Err(reason) => return Ok(reason),
+    };
 
-    database::add_default_word(&ctx.db, options.word).await?;
+    if !database::add_default_word(
+        &ctx.db,
+        word,
+        kind,
+        options.inflections.unwrap_or(false),
+        &options.pack.trim().to_lowercase(),
+    )
+    .await?
+    {
+        return Ok("this word is already a default word!".to_owned());
+    }
+    filter::invalidate_all(ctx);
 
-    Ok("done!")
+    Ok("done!".to_owned())
 }
@@ -7,11 +7,14 @@ use twilight_model::{
 };
 use twilight_util::builder::InteractionResponseDataBuilder;
 
-use crate::{database, Context};
+use crate::{
+    auto_spoiler::{self, Spoilered, ALLOW_CUSTOM_ID},
+    database, filter, Context,
+};
 
 pub async fn run(
     ctx: &Context,
-    mut component: MessageComponentInteraction,
+    component: MessageComponentInteraction,
 ) -> Result<InteractionResponse> {
     if !component
         .member
@@ -31,15 +34,47 @@ pub async fn run(
         });
     }
 
-    database::delete_word(&ctx.db, component.data.custom_id.parse()?).await?;
-    component.message.content.retain(|c| c != '|');
+    let guild_id = component
+        .guild_id
+        .context("component interaction doesn't have a guild id")?;
+
+    let word_ids = if component.data.custom_id == ALLOW_CUSTOM_ID {
+        component
+            .data
+            .values
+            .iter()
+            .map(|value| value.parse())
+            .collect::<Result<Vec<i32>, _>>()?
+    } else {
+        vec![component.data.custom_id.parse()?]
+    };
+
+    let content = filter::get_in_channel(ctx, guild_id, component.channel_id)
+        .await?
+        .map_or_else(
+            || component.message.content.clone(),
+            |filter| auto_spoiler::unspoiler_words(&filter, &component.message.content),
+        );
+
+    for word_id in word_ids {
+        database::allow_word(&ctx.db, guild_id, word_id).await?;
+    }
+    filter::invalidate(ctx, guild_id);
+
+    let spoilered = filter::get_in_channel(ctx, guild_id, component.channel_id)
+        .await?
+        .and_then(|filter| auto_spoiler::spoiler_words(&filter, &content))
+        .unwrap_or(Spoilered {
+            content,
+            components: vec![],
+        });
 
     Ok(InteractionResponse {
         kind: InteractionResponseType::UpdateMessage,
         data: Some(
             InteractionResponseDataBuilder::new()
-                .content(component.message.content)
-                .components([])
+                .content(spoilered.content)
+                .components(spoilered.components)
                 .flags(MessageFlags::EPHEMERAL)
                 .build(),
         ),
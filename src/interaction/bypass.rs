@@ -0,0 +1,101 @@
+use anyhow::{Context as _, Result};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    guild::Permissions,
+    id::{marker::RoleMarker, Id},
+};
+
+use crate::{bypass, database, Context};
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "bypass",
+    desc = "see or change the roles whose messages i never put in spoilers"
+)]
+pub enum Bypass {
+    #[command(name = "list")]
+    List(BypassList),
+    #[command(name = "add_role")]
+    AddRole(BypassAddRole),
+    #[command(name = "remove_role")]
+    RemoveRole(BypassRemoveRole),
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "list",
+    desc = "see the roles and the number of members that opted out"
+)]
+pub struct BypassList;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "add_role",
+    desc = "never put the messages of members with this role in spoilers, like staff"
+)]
+pub struct BypassAddRole {
+    #[command(name = "role", desc = "the role to bypass")]
+    role: Id<RoleMarker>,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "remove_role", desc = "remove one of the bypass roles")]
+pub struct BypassRemoveRole {
+    #[command(name = "role", desc = "the role to remove")]
+    role: Id<RoleMarker>,
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<String> {
+    if !command
+        .member
+        .context("command doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok("you need the manage guild permission to use this".to_owned());
+    }
+    let guild_id = command
+        .guild_id
+        .context("command doesn't have a guild id")?;
+
+    match Bypass::from_interaction(command.data.into())? {
+        Bypass::List(_) => {
+            let role_ids = database::bypass_roles(&ctx.db, guild_id).await?;
+            let opted_out_count = database::opted_out_users(&ctx.db, guild_id).await?.len();
+
+            let mut content = if role_ids.is_empty() {
+                "there are no bypass roles\n".to_owned()
+            } else {
+                let mut roles = "**roles whose messages i never put in spoilers**\n".to_owned();
+                for role_id in role_ids {
+                    roles.push_str("- <@&");
+                    roles.push_str(&role_id.to_string());
+                    roles.push_str(">\n");
+                }
+                roles
+            };
+            content.push_str(&opted_out_count.to_string());
+            content.push_str(" members opted out with `/opt_out`");
+
+            Ok(content)
+        }
+        Bypass::AddRole(options) => {
+            if !database::add_bypass_role(&ctx.db, guild_id, options.role).await? {
+                return Ok("this role is already added!".to_owned());
+            }
+            bypass::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Bypass::RemoveRole(options) => {
+            if !database::remove_bypass_role(&ctx.db, guild_id, options.role).await? {
+                return Ok("this role isn't a bypass role".to_owned());
+            }
+            bypass::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+    }
+}
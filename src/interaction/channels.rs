@@ -0,0 +1,355 @@
+use anyhow::{Context as _, Result};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::{
+        command::CommandOptionChoice,
+        interaction::{ApplicationCommand, ApplicationCommandAutocomplete},
+    },
+    guild::Permissions,
+    id::{marker::ChannelMarker, Id},
+};
+
+use crate::{
+    database::{self, WordKind},
+    filter,
+    interaction::{code, validate},
+    Context,
+};
+
+/// the maximum number of characters in the settings of a channel
+const MAX_LIST_LEN: usize = 2000;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "channels",
+    desc = "change how words are put in spoilers in a channel or category"
+)]
+pub enum Channels {
+    #[command(name = "info")]
+    Info(ChannelsInfo),
+    #[command(name = "enable")]
+    Enable(ChannelsEnable),
+    #[command(name = "disable")]
+    Disable(ChannelsDisable),
+    #[command(name = "add_pack")]
+    AddPack(ChannelsAddPack),
+    #[command(name = "remove_pack")]
+    RemovePack(ChannelsRemovePack),
+    #[command(name = "add_word")]
+    AddWord(ChannelsAddWord),
+    #[command(name = "remove_word")]
+    RemoveWord(ChannelsRemoveWord),
+    #[command(name = "reset")]
+    Reset(ChannelsReset),
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "info", desc = "see the settings of a channel or category")]
+pub struct ChannelsInfo {
+    #[command(
+        name = "channel",
+        desc = "the channel or category",
+        channel_types = "guild_text guild_news guild_voice guild_category"
+    )]
+    channel: Id<ChannelMarker>,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "put words in spoilers in a channel or category, even if its category is disabled"
+)]
+pub struct ChannelsEnable {
+    #[command(
+        name = "channel",
+        desc = "the channel or category",
+        channel_types = "guild_text guild_news guild_voice guild_category"
+    )]
+    channel: Id<ChannelMarker>,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "never put words in spoilers in a channel or category, like a venting channel"
+)]
+pub struct ChannelsDisable {
+    #[command(
+        name = "channel",
+        desc = "the channel or category",
+        channel_types = "guild_text guild_news guild_voice guild_category"
+    )]
+    channel: Id<ChannelMarker>,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "add_pack",
+    desc = "only use the default words in this pack and the other added packs in a channel"
+)]
+pub struct ChannelsAddPack {
+    #[command(
+        name = "channel",
+        desc = "the channel or category",
+        channel_types = "guild_text guild_news guild_voice guild_category"
+    )]
+    channel: Id<ChannelMarker>,
+    #[command(name = "pack", desc = "the pack to use", autocomplete = true)]
+    pack: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "remove_pack",
+    desc = "stop using a pack in a channel, it uses your packs again if none are left"
+)]
+pub struct ChannelsRemovePack {
+    #[command(
+        name = "channel",
+        desc = "the channel or category",
+        channel_types = "guild_text guild_news guild_voice guild_category"
+    )]
+    channel: Id<ChannelMarker>,
+    #[command(name = "pack", desc = "the pack to stop using", autocomplete = true)]
+    pack: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "add_word",
+    desc = "also put a word in spoilers only in a channel or category"
+)]
+pub struct ChannelsAddWord {
+    #[command(
+        name = "channel",
+        desc = "the channel or category",
+        channel_types = "guild_text guild_news guild_voice guild_category"
+    )]
+    channel: Id<ChannelMarker>,
+    #[command(
+        name = "word",
+        desc = "the word to add, add * to its end or start to also censor words starting or ending with it"
+    )]
+    word: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "remove_word",
+    desc = "remove a word added to a channel or category"
+)]
+pub struct ChannelsRemoveWord {
+    #[command(
+        name = "channel",
+        desc = "the channel or category",
+        channel_types = "guild_text guild_news guild_voice guild_category"
+    )]
+    channel: Id<ChannelMarker>,
+    #[command(name = "word", desc = "the word to remove")]
+    word: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "reset",
+    desc = "remove the settings, packs and words of a channel or category"
+)]
+pub struct ChannelsReset {
+    #[command(
+        name = "channel",
+        desc = "the channel or category",
+        channel_types = "guild_text guild_news guild_voice guild_category"
+    )]
+    channel: Id<ChannelMarker>,
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<String> {
+    if !command
+        .member
+        .context("command doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok("you need the manage guild permission to use this".to_owned());
+    }
+    let guild_id = command
+        .guild_id
+        .context("command doesn't have a guild id")?;
+
+    match Channels::from_interaction(command.data.into())? {
+        Channels::Info(options) => info(ctx, options.channel).await,
+        Channels::Enable(options) => {
+            database::set_channel_enabled(&ctx.db, guild_id, options.channel, true).await?;
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Channels::Disable(options) => {
+            database::set_channel_enabled(&ctx.db, guild_id, options.channel, false).await?;
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Channels::AddPack(options) => {
+            if !database::add_channel_pack(
+                &ctx.db,
+                guild_id,
+                options.channel,
+                &options.pack.trim().to_lowercase(),
+            )
+            .await?
+            {
+                return Ok("there's no such pack or it's already added".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Channels::RemovePack(options) => {
+            if !database::remove_channel_pack(
+                &ctx.db,
+                guild_id,
+                options.channel,
+                &options.pack.trim().to_lowercase(),
+            )
+            .await?
+            {
+                return Ok("this pack isn't added to the channel".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Channels::AddWord(options) => {
+            let (word, kind) = match validate::word(&options.word, false, false) {
+                Ok(parsed) => parsed,
+                Err(reason) => return Ok(reason),
+            };
+            if let Err(reason) = validate::quota(
+                database::custom_word_count(&ctx.db, guild_id).await?,
+                database::regex_word_count(&ctx.db, guild_id).await?,
+                kind,
+            ) {
+                return Ok(reason);
+            }
+
+            if !database::add_channel_word(&ctx.db, guild_id, options.channel, word, kind).await? {
+                return Ok("this word is already added!".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Channels::RemoveWord(options) => {
+            let (word, kind) = WordKind::parse(&options.word);
+            if !database::remove_channel_word(&ctx.db, guild_id, options.channel, &word, kind)
+                .await?
+            {
+                return Ok("this word isn't added to the channel".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Channels::Reset(options) => {
+            if !database::reset_channel(&ctx.db, guild_id, options.channel).await? {
+                return Ok("this channel doesn't have any settings".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+    }
+}
+
+/// returns the settings of the channel, or which settings it uses if it
+/// doesn't have any
+async fn info(ctx: &Context, channel_id: Id<ChannelMarker>) -> Result<String> {
+    let Some(settings) = database::channel_settings(&ctx.db, &[channel_id])
+        .await?
+        .into_iter()
+        .next()
+    else {
+        return Ok(format!(
+            "<#{channel_id}> doesn't have any settings, so it uses the settings of its \
+             category if it has any, or else your settings, threads and forum posts use the \
+             settings of their channel"
+        ));
+    };
+
+    let mut content = format!(
+        "**settings of <#{channel_id}>**\n{}",
+        match settings.enabled {
+            Some(true) => "words are put in spoilers\n",
+            Some(false) => "words are never put in spoilers\n",
+            None => "puts words in spoilers if its category does\n",
+        }
+    );
+
+    let packs = database::channel_packs(&ctx.db, channel_id).await?;
+    if packs.is_empty() {
+        content.push_str("uses your packs\n");
+    } else {
+        content.push_str("only uses the packs: ");
+        content.push_str(&packs.join(", "));
+        content.push('\n');
+    }
+
+    let words = database::channel_words(&ctx.db, channel_id).await?;
+    if !words.is_empty() {
+        content.push_str("also puts these words in spoilers:");
+        for word in words {
+            let line = format!("\n- {}", code(&word.to_string()));
+            if content.len().saturating_add(line.len()) > MAX_LIST_LEN {
+                break;
+            }
+            content.push_str(&line);
+        }
+    }
+
+    Ok(content)
+}
+
+/// suggests the packs to add to or remove from a channel
+pub async fn autocomplete(
+    ctx: &Context,
+    autocomplete: ApplicationCommandAutocomplete,
+) -> Result<Vec<CommandOptionChoice>> {
+    if !autocomplete
+        .member
+        .context("autocomplete interaction doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok(vec![]);
+    }
+    let guild_id = autocomplete
+        .guild_id
+        .context("autocomplete interaction doesn't have a guild id")?;
+
+    let input = autocomplete
+        .data
+        .options
+        .first()
+        .context("autocomplete interaction doesn't have a subcommand")?
+        .options
+        .iter()
+        .find(|option| option.focused)
+        .and_then(|option| option.value.as_deref())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    Ok(database::packs(&ctx.db, guild_id)
+        .await?
+        .into_iter()
+        .filter(|pack| pack.name.contains(&input))
+        .take(25)
+        .map(|pack| CommandOptionChoice::String {
+            name: pack.name.clone(),
+            name_localizations: None,
+            value: pack.name,
+        })
+        .collect())
+}
@@ -0,0 +1,225 @@
+use anyhow::{Context as _, Result};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::{
+    command::CommandOptionChoice,
+    interaction::{application_command::CommandData, ApplicationCommandAutocomplete},
+};
+
+use crate::{
+    database::{self, WordKind},
+    filter,
+    interaction::{code, push_list, validate},
+    Context,
+};
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "default_words", desc = "see or change the default words")]
+pub enum DefaultWords {
+    #[command(name = "list")]
+    List(DefaultWordsList),
+    #[command(name = "remove")]
+    Remove(DefaultWordsRemove),
+    #[command(name = "rename")]
+    Rename(DefaultWordsRename),
+    #[command(name = "move")]
+    Move(DefaultWordsMove),
+    #[command(name = "allowed_by")]
+    AllowedBy(DefaultWordsAllowedBy),
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "see the default words and their packs")]
+pub struct DefaultWordsList {
+    #[command(
+        name = "pack",
+        desc = "only see the words in this pack",
+        autocomplete = true
+    )]
+    pack: Option<String>,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "remove", desc = "remove a default word")]
+pub struct DefaultWordsRemove {
+    #[command(name = "word", desc = "the word to remove", autocomplete = true)]
+    word: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "rename",
+    desc = "change a default word, keeping it allowed where it's allowed"
+)]
+pub struct DefaultWordsRename {
+    #[command(name = "word", desc = "the word to change", autocomplete = true)]
+    word: String,
+    #[command(
+        name = "new_word",
+        desc = "the new word, add * to its end or start to also censor words starting or ending with it"
+    )]
+    new_word: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "move", desc = "move a default word to another pack")]
+pub struct DefaultWordsMove {
+    #[command(name = "word", desc = "the word to move", autocomplete = true)]
+    word: String,
+    #[command(
+        name = "pack",
+        desc = "the pack to move the word to, it's created if it doesn't exist",
+        autocomplete = true
+    )]
+    pack: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "allowed_by",
+    desc = "see the servers that allowed a default word"
+)]
+pub struct DefaultWordsAllowedBy {
+    #[command(name = "word", desc = "the allowed word", autocomplete = true)]
+    word: String,
+}
+
+pub async fn run(ctx: &Context, data: CommandData) -> Result<String> {
+    match DefaultWords::from_interaction(data.into())? {
+        DefaultWords::List(options) => {
+            let pack = options.pack.map(|pack| pack.trim().to_lowercase());
+            let words = database::default_words(&ctx.db)
+                .await?
+                .into_iter()
+                .filter(|word| pack.is_none() || word.origin == pack)
+                .collect::<Vec<_>>();
+            if words.is_empty() {
+                return Ok("there are no such words".to_owned());
+            }
+
+            let mut content = format!("**{} default words**\n", words.len());
+            push_list(
+                &mut content,
+                words
+                    .iter()
+                    .map(|word| {
+                        format!(
+                            "{} ({})",
+                            code(&word.to_string()),
+                            word.origin.as_deref().unwrap_or_default()
+                        )
+                    })
+                    .collect(),
+            );
+
+            Ok(content)
+        }
+        DefaultWords::Remove(options) => {
+            let (word, kind) = WordKind::parse(&options.word);
+            if !database::remove_default_word(&ctx.db, &word, kind).await? {
+                return Ok("there's no such default word".to_owned());
+            }
+            filter::invalidate_all(ctx);
+
+            Ok("done!".to_owned())
+        }
+        DefaultWords::Rename(options) => {
+            let (word, kind) = WordKind::parse(&options.word);
+            let (new_word, new_kind) = match validate::word(&options.new_word, false, false) {
+                Ok(parsed) => parsed,
+                Err(reason) => return Ok(reason),
+            };
+
+            let words = database::default_words(&ctx.db).await?;
+            if words
+                .iter()
+                .any(|w| w.word == new_word && !(w.word == word && w.kind == kind))
+            {
+                return Ok("the new word is already a default word!".to_owned());
+            }
+
+            if !database::rename_default_word(&ctx.db, &word, kind, new_word, new_kind).await? {
+                return Ok("there's no such default word".to_owned());
+            }
+            filter::invalidate_all(ctx);
+
+            Ok("done!".to_owned())
+        }
+        DefaultWords::Move(options) => {
+            let (word, kind) = WordKind::parse(&options.word);
+            if !database::move_default_word(
+                &ctx.db,
+                &word,
+                kind,
+                &options.pack.trim().to_lowercase(),
+            )
+            .await?
+            {
+                return Ok("there's no such default word".to_owned());
+            }
+            filter::invalidate_all(ctx);
+
+            Ok("done!".to_owned())
+        }
+        DefaultWords::AllowedBy(options) => {
+            let (word, kind) = WordKind::parse(&options.word);
+            let guild_ids = database::allowing_guilds(&ctx.db, &word, kind).await?;
+            if guild_ids.is_empty() {
+                return Ok("no server allowed this word".to_owned());
+            }
+
+            let mut content = format!("**{} servers allowed this word**\n", guild_ids.len());
+            push_list(
+                &mut content,
+                guild_ids
+                    .into_iter()
+                    .map(|guild_id| {
+                        ctx.cache.guild(guild_id).map_or_else(
+                            || guild_id.to_string(),
+                            |guild| format!("{} ({guild_id})", guild.name()),
+                        )
+                    })
+                    .collect(),
+            );
+
+            Ok(content)
+        }
+    }
+}
+
+/// suggests the default words or their packs
+pub async fn autocomplete(
+    ctx: &Context,
+    autocomplete: ApplicationCommandAutocomplete,
+) -> Result<Vec<CommandOptionChoice>> {
+    let focused = autocomplete
+        .data
+        .options
+        .first()
+        .context("autocomplete interaction doesn't have a subcommand")?
+        .options
+        .iter()
+        .find(|option| option.focused)
+        .context("autocomplete interaction doesn't have a focused option")?;
+    let input = focused.value.as_deref().unwrap_or_default().to_lowercase();
+
+    let choices = if focused.name == "pack" {
+        database::pack_names(&ctx.db).await?
+    } else {
+        database::default_words(&ctx.db)
+            .await?
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    };
+
+    Ok(choices
+        .into_iter()
+        .filter(|choice| choice.contains(&input) && choice.chars().count() <= 100)
+        .take(25)
+        .map(|choice| CommandOptionChoice::String {
+            name: choice.clone(),
+            name_localizations: None,
+            value: choice,
+        })
+        .collect())
+}
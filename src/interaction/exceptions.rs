@@ -0,0 +1,154 @@
+use anyhow::{Context as _, Result};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::{
+        command::CommandOptionChoice,
+        interaction::{ApplicationCommand, ApplicationCommandAutocomplete},
+    },
+    guild::Permissions,
+};
+
+use crate::{
+    database, filter,
+    interaction::{code, push_list, validate},
+    Context,
+};
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "exceptions",
+    desc = "see or change the words i never put in spoilers"
+)]
+pub enum Exceptions {
+    #[command(name = "list")]
+    List(ExceptionsList),
+    #[command(name = "add")]
+    Add(ExceptionsAdd),
+    #[command(name = "remove")]
+    Remove(ExceptionsRemove),
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "see the words i never put in spoilers")]
+pub struct ExceptionsList;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "add",
+    desc = "never put words in spoilers when they're part of this word, like a place name"
+)]
+pub struct ExceptionsAdd {
+    #[command(name = "exception", desc = "the word to never put in spoilers")]
+    exception: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "remove", desc = "remove one of the exceptions")]
+pub struct ExceptionsRemove {
+    #[command(
+        name = "exception",
+        desc = "the exception to remove",
+        autocomplete = true
+    )]
+    exception: String,
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<String> {
+    if !command
+        .member
+        .context("command doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok("you need the manage guild permission to use this".to_owned());
+    }
+    let guild_id = command
+        .guild_id
+        .context("command doesn't have a guild id")?;
+
+    match Exceptions::from_interaction(command.data.into())? {
+        Exceptions::List(_) => {
+            let mut exceptions = database::exceptions(&ctx.db, guild_id).await?;
+            if exceptions.is_empty() {
+                return Ok("there are no exceptions".to_owned());
+            }
+            exceptions.sort_unstable();
+
+            let mut content = "**words i never put in spoilers**\n".to_owned();
+            push_list(
+                &mut content,
+                exceptions.iter().map(|exception| code(exception)).collect(),
+            );
+
+            Ok(content)
+        }
+        Exceptions::Add(options) => {
+            let exception = match validate::exception(&options.exception) {
+                Ok(exception) => exception,
+                Err(reason) => return Ok(reason),
+            };
+
+            if !database::add_exception(&ctx.db, guild_id, &exception).await? {
+                return Ok("this exception is already added!".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Exceptions::Remove(options) => {
+            let Ok(exception) = validate::exception(&options.exception) else {
+                return Ok("there's no such exception".to_owned());
+            };
+            if !database::remove_exception(&ctx.db, guild_id, &exception).await? {
+                return Ok("there's no such exception".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+    }
+}
+
+/// suggests the exceptions to remove
+pub async fn autocomplete(
+    ctx: &Context,
+    autocomplete: ApplicationCommandAutocomplete,
+) -> Result<Vec<CommandOptionChoice>> {
+    if !autocomplete
+        .member
+        .context("autocomplete interaction doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok(vec![]);
+    }
+    let guild_id = autocomplete
+        .guild_id
+        .context("autocomplete interaction doesn't have a guild id")?;
+
+    let input = autocomplete
+        .data
+        .options
+        .first()
+        .context("autocomplete interaction doesn't have a subcommand")?
+        .options
+        .iter()
+        .find(|option| option.focused)
+        .and_then(|option| option.value.as_deref())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    Ok(database::exceptions(&ctx.db, guild_id)
+        .await?
+        .into_iter()
+        .filter(|exception| exception.contains(&input))
+        .take(25)
+        .map(|exception| CommandOptionChoice::String {
+            name: exception.clone(),
+            name_localizations: None,
+            value: exception,
+        })
+        .collect())
+}
@@ -0,0 +1,39 @@
+use anyhow::{Context as _, Result};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::interaction::ApplicationCommand;
+
+use crate::{bypass, database, Context};
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "opt_out",
+    desc = "choose whether i put words in your messages in spoilers in this server"
+)]
+pub struct OptOut {
+    #[command(
+        name = "opted_out",
+        desc = "set true to never have your messages put in spoilers, or false to undo it"
+    )]
+    opted_out: bool,
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<String> {
+    let guild_id = command
+        .guild_id
+        .context("command doesn't have a guild id")?;
+    let user_id = command
+        .author_id()
+        .context("command doesn't have an author")?;
+    let options = OptOut::from_interaction(command.data.into())?;
+
+    if !database::set_opted_out(&ctx.db, guild_id, user_id, options.opted_out).await? {
+        return Ok(if options.opted_out {
+            "you already opted out!".to_owned()
+        } else {
+            "you didn't opt out".to_owned()
+        });
+    }
+    bypass::invalidate(ctx, guild_id);
+
+    Ok("done!".to_owned())
+}
@@ -0,0 +1,144 @@
+use anyhow::{Context as _, Result};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::{
+        command::CommandOptionChoice,
+        interaction::{ApplicationCommand, ApplicationCommandAutocomplete},
+    },
+    guild::Permissions,
+};
+
+use crate::{database, filter, Context};
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "packs",
+    desc = "see or choose the packs of default words i put in spoilers"
+)]
+pub enum Packs {
+    #[command(name = "list")]
+    List(PacksList),
+    #[command(name = "enable")]
+    Enable(PacksEnable),
+    #[command(name = "disable")]
+    Disable(PacksDisable),
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "see the packs and whether they're enabled")]
+pub struct PacksList;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "enable", desc = "put the words in a pack in spoilers")]
+pub struct PacksEnable {
+    #[command(name = "pack", desc = "the pack to enable", autocomplete = true)]
+    pack: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "stop putting the words in a pack in spoilers"
+)]
+pub struct PacksDisable {
+    #[command(name = "pack", desc = "the pack to disable", autocomplete = true)]
+    pack: String,
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<String> {
+    if !command
+        .member
+        .context("command doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok("you need the manage guild permission to use this".to_owned());
+    }
+    let guild_id = command
+        .guild_id
+        .context("command doesn't have a guild id")?;
+
+    let (pack, enabled) = match Packs::from_interaction(command.data.into())? {
+        Packs::List(_) => {
+            let packs = database::packs(&ctx.db, guild_id).await?;
+            if packs.is_empty() {
+                return Ok("there are no packs yet".to_owned());
+            }
+
+            let mut content = "**packs**\n".to_owned();
+            for pack in packs {
+                content.push_str("- ");
+                content.push_str(&pack.name);
+                content.push_str(if pack.enabled {
+                    " (enabled, "
+                } else {
+                    " (disabled, "
+                });
+                content.push_str(&pack.word_count.to_string());
+                content.push_str(" words)");
+                if !pack.description.is_empty() {
+                    content.push_str(": ");
+                    content.push_str(&pack.description);
+                }
+                content.push('\n');
+            }
+
+            return Ok(content);
+        }
+        Packs::Enable(options) => (options.pack, true),
+        Packs::Disable(options) => (options.pack, false),
+    };
+
+    if !database::set_pack_enabled(&ctx.db, guild_id, &pack.trim().to_lowercase(), enabled).await? {
+        return Ok("there's no such pack".to_owned());
+    }
+    filter::invalidate(ctx, guild_id);
+
+    Ok("done!".to_owned())
+}
+
+/// suggests the packs to enable or disable
+pub async fn autocomplete(
+    ctx: &Context,
+    autocomplete: ApplicationCommandAutocomplete,
+) -> Result<Vec<CommandOptionChoice>> {
+    if !autocomplete
+        .member
+        .context("autocomplete interaction doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok(vec![]);
+    }
+    let guild_id = autocomplete
+        .guild_id
+        .context("autocomplete interaction doesn't have a guild id")?;
+
+    let subcommand = autocomplete
+        .data
+        .options
+        .first()
+        .context("autocomplete interaction doesn't have a subcommand")?;
+    let enabling = subcommand.name == "enable";
+    let input = subcommand
+        .options
+        .iter()
+        .find(|option| option.focused)
+        .and_then(|option| option.value.as_deref())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    Ok(database::packs(&ctx.db, guild_id)
+        .await?
+        .into_iter()
+        .filter(|pack| pack.enabled != enabling && pack.name.contains(&input))
+        .take(25)
+        .map(|pack| CommandOptionChoice::String {
+            name: pack.name.clone(),
+            name_localizations: None,
+            value: pack.name,
+        })
+        .collect())
+}
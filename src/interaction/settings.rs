@@ -0,0 +1,88 @@
+use anyhow::{Context as _, Result};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{application::interaction::ApplicationCommand, guild::Permissions};
+
+use crate::{database, filter, filter::normalize::Normalization, Context};
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "settings",
+    desc = "change how i put words in spoilers, or see the current settings"
+)]
+pub struct Settings {
+    #[command(
+        name = "spoiler_attachments",
+        desc = "set true to also put the files of the messages i put in spoilers in spoilers"
+    )]
+    spoiler_attachments: Option<bool>,
+    #[command(
+        name = "reply_context",
+        desc = "set false to not show which message the messages i repost were replying to"
+    )]
+    reply_context: Option<bool>,
+    #[command(
+        name = "spoiler_edits",
+        desc = "set true to also put words in spoilers when they're edited into a message"
+    )]
+    spoiler_edits: Option<bool>,
+    #[command(
+        name = "normalize",
+        desc = "set true to also catch words hidden with look-alike letters, spaces or leetspeak"
+    )]
+    normalize: Option<bool>,
+    #[command(
+        name = "leet_map",
+        desc = "the characters to replace when normalizing, like `4a 3e` to read 4 as a and 3 as e"
+    )]
+    leet_map: Option<String>,
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<String> {
+    if !command
+        .member
+        .context("command doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok("you need the manage guild permission to use this".to_owned());
+    }
+    let guild_id = command
+        .guild_id
+        .context("command doesn't have a guild id")?;
+
+    let options = Settings::from_interaction(command.data.into())?;
+    let mut settings = database::settings(&ctx.db, guild_id).await?;
+
+    if let Some(spoiler_attachments) = options.spoiler_attachments {
+        settings.spoiler_attachments = spoiler_attachments;
+    }
+    if let Some(reply_context) = options.reply_context {
+        settings.reply_context = reply_context;
+    }
+    if let Some(spoiler_edits) = options.spoiler_edits {
+        settings.spoiler_edits = spoiler_edits;
+    }
+    if let Some(normalize) = options.normalize {
+        settings.normalize = normalize;
+    }
+    if let Some(leet_map) = options.leet_map {
+        if let Err(err) = Normalization::new(&leet_map) {
+            return Ok(err.to_string());
+        }
+        settings.leet_map = leet_map;
+    }
+
+    database::set_settings(&ctx.db, guild_id, &settings).await?;
+    filter::invalidate(ctx, guild_id);
+
+    Ok(format!(
+        "current settings:\n- spoiler attachments: {}\n- reply context: {}\n- spoiler edits: \
+         {}\n- normalize: {}\n- leet map: `{}`",
+        settings.spoiler_attachments,
+        settings.reply_context,
+        settings.spoiler_edits,
+        settings.normalize,
+        settings.leet_map
+    ))
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context as _, Result};
+use rand::{distributions::Alphanumeric, Rng};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::{
+        command::CommandOptionChoice,
+        interaction::{ApplicationCommand, ApplicationCommandAutocomplete},
+    },
+    guild::Permissions,
+};
+
+use crate::{database, filter, Context};
+
+/// the number of characters in a share code
+const CODE_LEN: usize = 10;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "share",
+    desc = "share your custom words with other servers or use theirs"
+)]
+pub enum Share {
+    #[command(name = "info")]
+    Info(ShareInfo),
+    #[command(name = "publish")]
+    Publish(SharePublish),
+    #[command(name = "unpublish")]
+    Unpublish(ShareUnpublish),
+    #[command(name = "subscribe")]
+    Subscribe(ShareSubscribe),
+    #[command(name = "unsubscribe")]
+    Unsubscribe(ShareUnsubscribe),
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "info",
+    desc = "see your share code and the lists you subscribed to"
+)]
+pub struct ShareInfo;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "publish",
+    desc = "get a code other servers can use to put your custom words in spoilers"
+)]
+pub struct SharePublish;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "unpublish",
+    desc = "stop sharing your custom words, removing them from the servers using them"
+)]
+pub struct ShareUnpublish;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "subscribe",
+    desc = "also put the custom words of another server in spoilers"
+)]
+pub struct ShareSubscribe {
+    #[command(name = "code", desc = "the share code of the other server")]
+    code: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "unsubscribe",
+    desc = "stop putting the custom words of another server in spoilers"
+)]
+pub struct ShareUnsubscribe {
+    #[command(
+        name = "code",
+        desc = "the share code of the other server",
+        autocomplete = true
+    )]
+    code: String,
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<String> {
+    if !command
+        .member
+        .context("command doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok("you need the manage guild permission to use this".to_owned());
+    }
+    let guild_id = command
+        .guild_id
+        .context("command doesn't have a guild id")?;
+
+    match Share::from_interaction(command.data.into())? {
+        Share::Info(_) => {
+            let mut content = database::share_code(&ctx.db, guild_id).await?.map_or_else(
+                || "you didn't publish your list\n".to_owned(),
+                |code| format!("your share code is `{code}`\n"),
+            );
+
+            let subscriptions = database::subscriptions(&ctx.db, guild_id).await?;
+            if subscriptions.is_empty() {
+                content.push_str("you didn't subscribe to any list");
+            } else {
+                content.push_str("you subscribed to:");
+                for subscription in subscriptions {
+                    content.push_str("\n- `");
+                    content.push_str(&subscription.code);
+                    content.push('`');
+                }
+            }
+
+            Ok(content)
+        }
+        Share::Publish(_) => {
+            let code = database::publish(&ctx.db, guild_id, &new_code()).await?;
+
+            Ok(format!(
+                "your share code is `{code}`, other servers can use it with `/share subscribe` \
+                 to also put your custom words in spoilers"
+            ))
+        }
+        Share::Unpublish(_) => {
+            if !database::unpublish(&ctx.db, guild_id).await? {
+                return Ok("you didn't publish your list".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Share::Subscribe(options) => {
+            let Some(publisher_id) = database::publisher(&ctx.db, options.code.trim()).await?
+            else {
+                return Ok("there's no list with this code".to_owned());
+            };
+            if publisher_id == guild_id {
+                return Ok("you can't subscribe to your own list".to_owned());
+            }
+
+            if !database::subscribe(&ctx.db, guild_id, publisher_id).await? {
+                return Ok("you already subscribed to this list".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+        Share::Unsubscribe(options) => {
+            if !database::unsubscribe(&ctx.db, guild_id, options.code.trim()).await? {
+                return Ok("you didn't subscribe to this list".to_owned());
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok("done!".to_owned())
+        }
+    }
+}
+
+/// suggests the codes of the lists to unsubscribe from
+pub async fn autocomplete(
+    ctx: &Context,
+    autocomplete: ApplicationCommandAutocomplete,
+) -> Result<Vec<CommandOptionChoice>> {
+    if !autocomplete
+        .member
+        .context("autocomplete interaction doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok(vec![]);
+    }
+    let guild_id = autocomplete
+        .guild_id
+        .context("autocomplete interaction doesn't have a guild id")?;
+
+    Ok(database::subscriptions(&ctx.db, guild_id)
+        .await?
+        .into_iter()
+        .take(25)
+        .map(|subscription| CommandOptionChoice::String {
+            name: subscription.code.clone(),
+            name_localizations: None,
+            value: subscription.code,
+        })
+        .collect())
+}
+
+/// returns a random share code
+fn new_code() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(CODE_LEN)
+        .map(|c| char::from(c).to_ascii_lowercase())
+        .collect()
+}
@@ -0,0 +1,156 @@
+use anyhow::{Context as _, Result};
+use twilight_model::{
+    application::{
+        component::{button::ButtonStyle, ActionRow, Button, Component},
+        interaction::MessageComponentInteraction,
+    },
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{
+        marker::{ChannelMarker, GuildMarker},
+        Id,
+    },
+    user::User,
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    database::{self, Suggestion, SuggestionStatus},
+    filter, Context,
+};
+
+/// the start of the custom ids of the buttons to review a suggestion
+pub const CUSTOM_ID_PREFIX: &str = "suggestion:";
+
+/// the pack approved suggestions are added to
+const PACK: &str = "general";
+
+/// records the suggestion, sending it to the owner to review if no guild
+/// suggested it before
+pub async fn suggest(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    user: &User,
+    suggestion: &Suggestion,
+) -> Result<()> {
+    let Some(suggestion_id) = database::suggest(&ctx.db, guild_id, channel_id, suggestion).await?
+    else {
+        return Ok(());
+    };
+
+    ctx.http
+        .create_message(ctx.owner_channel_id)
+        .content(&format!(
+            "{}#{} suggested a word: {suggestion}",
+            user.name, user.discriminator
+        ))?
+        .components(&[Component::ActionRow(ActionRow {
+            components: vec![
+                review_button("approve", "approve", suggestion_id, ButtonStyle::Success),
+                review_button(
+                    "approve without notifying",
+                    "approve_quietly",
+                    suggestion_id,
+                    ButtonStyle::Secondary,
+                ),
+                review_button("reject", "reject", suggestion_id, ButtonStyle::Danger),
+            ],
+        })])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// approves or rejects the suggestion, adding it to the default words and
+/// notifying the guilds that suggested it if it's approved
+pub async fn review(
+    ctx: &Context,
+    mut component: MessageComponentInteraction,
+) -> Result<InteractionResponse> {
+    let (action, id) = component
+        .data
+        .custom_id
+        .strip_prefix(CUSTOM_ID_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+        .context("custom id isn't a suggestion custom id")?;
+    let suggestion_id = id.parse()?;
+    let status = if action == "reject" {
+        SuggestionStatus::Rejected
+    } else {
+        SuggestionStatus::Approved
+    };
+
+    let outcome = match database::review_suggestion(&ctx.db, suggestion_id, status).await? {
+        None => "it was already reviewed",
+        Some(_) if status == SuggestionStatus::Rejected => "rejected",
+        Some(suggestion) => {
+            database::add_default_word(
+                &ctx.db,
+                suggestion.word.clone(),
+                suggestion.kind,
+                suggestion.inflections,
+                PACK,
+            )
+            .await?;
+            filter::invalidate_all(ctx);
+
+            if action != "approve_quietly" {
+                notify(ctx, suggestion_id, &suggestion).await?;
+            }
+
+            "approved"
+        }
+    };
+
+    component.message.content.push_str("\n\n");
+    component.message.content.push_str(outcome);
+
+    Ok(InteractionResponse {
+        kind: InteractionResponseType::UpdateMessage,
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .content(component.message.content)
+                .components([])
+                .build(),
+        ),
+    })
+}
+
+/// tells the guilds that suggested the word that it was added to the default
+/// words, only logging the errors so that the other guilds are still notified
+#[allow(clippy::print_stderr)]
+async fn notify(ctx: &Context, suggestion_id: i32, suggestion: &Suggestion) -> Result<()> {
+    let content = format!(
+        "`{suggestion}`, the word this server suggested, is now a default word, thank you!"
+    );
+
+    for channel_id in database::suggesting_channels(&ctx.db, suggestion_id).await? {
+        if let Err(err) = send(ctx, channel_id, &content).await {
+            eprintln!("{err:#?}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn send(ctx: &Context, channel_id: Id<ChannelMarker>, content: &str) -> Result<()> {
+    ctx.http
+        .create_message(channel_id)
+        .content(content)?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+fn review_button(label: &str, action: &str, suggestion_id: i32, style: ButtonStyle) -> Component {
+    Component::Button(Button {
+        custom_id: Some(format!("{CUSTOM_ID_PREFIX}{action}:{suggestion_id}")),
+        disabled: false,
+        emoji: None,
+        label: Some(label.to_owned()),
+        style,
+        url: None,
+    })
+}
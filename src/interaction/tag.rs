@@ -71,6 +71,7 @@ pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<&'static
         thread_id,
         &format!("{} {}", options.message, options.tag.value()),
         &[],
+        &[],
     )
     .await?;
 
@@ -42,6 +42,7 @@ pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<&'static
         thread_id,
         &format!("tw {} ||{}||", options.tw_type, options.message),
         &[],
+        &[],
     )
     .await?;
 
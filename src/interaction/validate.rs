@@ -0,0 +1,201 @@
+use crate::{database::WordKind, filter};
+
+/// the minimum number of characters in a word
+const MIN_WORD_LEN: usize = 2;
+
+/// the maximum number of characters in a word
+const MAX_WORD_LEN: usize = 100;
+
+/// the maximum number of characters in a regex
+const MAX_REGEX_LEN: usize = 300;
+
+/// the maximum number of characters in an exception
+const MAX_EXCEPTION_LEN: usize = 100;
+
+/// the maximum number of custom words a guild can add, including the words
+/// added only to its channels
+const MAX_CUSTOM_WORDS: usize = 1000;
+
+/// the maximum number of regexes a guild can add, counted apart from the other
+/// custom words since they're much more expensive to match
+const MAX_REGEX_WORDS: usize = 25;
+
+/// parses the word the way it's added, returning the word and its kind, or
+/// why it can't be added
+///
+/// `regex` adds the word as a regex even if it isn't written between slashes
+/// and `match_inside_words` makes it a substring unless it's a regex
+pub fn word(
+    entry: &str,
+    regex: bool,
+    match_inside_words: bool,
+) -> Result<(String, WordKind), String> {
+    let (word, mut kind) = match WordKind::parse(entry) {
+        (_, parsed_kind) if regex && parsed_kind != WordKind::Regex => {
+            (entry.trim().to_owned(), WordKind::Regex)
+        }
+        parsed => parsed,
+    };
+
+    if word.is_empty() {
+        return Err("the word can't be empty".to_owned());
+    }
+    if word.contains(char::is_control) {
+        return Err("the word can't have control characters".to_owned());
+    }
+
+    if kind == WordKind::Regex {
+        if word.chars().count() > MAX_REGEX_LEN {
+            return Err(format!(
+                "regexes can't be longer than {MAX_REGEX_LEN} characters"
+            ));
+        }
+        if let Err(err) = filter::regex(&word) {
+            return Err(format!("this regex isn't valid:\n```\n{err}\n```"));
+        }
+
+        return Ok((word, kind));
+    }
+
+    let len = word.chars().count();
+    if len < MIN_WORD_LEN {
+        return Err(format!(
+            "words must be at least {MIN_WORD_LEN} characters long"
+        ));
+    }
+    if len > MAX_WORD_LEN {
+        return Err(format!(
+            "words can't be longer than {MAX_WORD_LEN} characters"
+        ));
+    }
+    if word.contains('*') {
+        return Err("words can only have `*` at their start or end".to_owned());
+    }
+    if word.contains(['|', '`']) {
+        return Err("words can't have `|` or `` ` `` in them".to_owned());
+    }
+
+    if match_inside_words {
+        kind = WordKind::Substring;
+    }
+
+    Ok((word, kind))
+}
+
+/// returns why a word of this kind can't be added when the guild has this
+/// many custom words and regexes, if it can't
+pub fn quota(word_count: usize, regex_count: usize, kind: WordKind) -> Result<(), String> {
+    if word_count >= MAX_CUSTOM_WORDS {
+        return Err(format!(
+            "you can't add more than {MAX_CUSTOM_WORDS} custom words"
+        ));
+    }
+    if kind == WordKind::Regex && regex_count >= MAX_REGEX_WORDS {
+        return Err(format!("you can't add more than {MAX_REGEX_WORDS} regexes"));
+    }
+
+    Ok(())
+}
+
+/// returns the lowercased exception, or why it can't be added
+pub fn exception(entry: &str) -> Result<String, String> {
+    let exception = entry
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    if exception.is_empty() {
+        return Err("the exception can't be empty".to_owned());
+    }
+    if exception.chars().count() > MAX_EXCEPTION_LEN {
+        return Err(format!(
+            "exceptions can't be longer than {MAX_EXCEPTION_LEN} characters"
+        ));
+    }
+
+    Ok(exception)
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_result_states)]
+mod tests {
+    use super::{
+        exception, quota, word, MAX_CUSTOM_WORDS, MAX_EXCEPTION_LEN, MAX_REGEX_LEN,
+        MAX_REGEX_WORDS, MAX_WORD_LEN,
+    };
+    use crate::database::WordKind;
+
+    #[test]
+    fn lengths() {
+        assert!(word("a", false, false).is_err());
+        assert!(word("*a*", false, false).is_err());
+        assert!(word("ab", false, false).is_ok());
+        assert!(word(&"a".repeat(MAX_WORD_LEN), false, false).is_ok());
+        assert!(word(&"a".repeat(MAX_WORD_LEN + 1), false, false).is_err());
+        // characters are counted, not bytes
+        assert!(word(&"\u{e9}".repeat(MAX_WORD_LEN), false, false).is_ok());
+    }
+
+    #[test]
+    fn empty() {
+        assert!(word("", false, false).is_err());
+        assert!(word("   ", false, false).is_err());
+        assert!(word("**", false, false).is_err());
+    }
+
+    #[test]
+    fn characters() {
+        assert!(word("ki*ll", false, false).is_err());
+        assert!(word("ki||ll", false, false).is_err());
+        assert!(word("`kill`", false, false).is_err());
+        assert!(word("ki\u{0}ll", false, false).is_err());
+        assert!(word("kill yourself", false, false).is_ok());
+    }
+
+    #[test]
+    fn normalized() {
+        assert_eq!(
+            word("  Kill   Yourself ", false, false),
+            Ok(("kill yourself".to_owned(), WordKind::Phrase))
+        );
+        assert_eq!(
+            word("kill", false, true),
+            Ok(("kill".to_owned(), WordKind::Substring))
+        );
+    }
+
+    #[test]
+    fn regexes() {
+        assert_eq!(
+            word("/k[i1]ll/", false, false),
+            Ok(("k[i1]ll".to_owned(), WordKind::Regex))
+        );
+        assert_eq!(
+            word("k[i1]ll", true, false),
+            Ok(("k[i1]ll".to_owned(), WordKind::Regex))
+        );
+        assert!(word("/k(ill/", false, false).is_err());
+        assert!(word(
+            &format!("/{}/", "a".repeat(MAX_REGEX_LEN + 1)),
+            false,
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn quotas() {
+        assert!(quota(MAX_CUSTOM_WORDS - 1, MAX_REGEX_WORDS - 1, WordKind::Regex).is_ok());
+        assert!(quota(MAX_CUSTOM_WORDS, 0, WordKind::Word).is_err());
+        assert!(quota(0, MAX_REGEX_WORDS, WordKind::Word).is_ok());
+        assert!(quota(0, MAX_REGEX_WORDS, WordKind::Regex).is_err());
+    }
+
+    #[test]
+    fn exceptions() {
+        assert_eq!(exception("  Skill   Issue "), Ok("skill issue".to_owned()));
+        assert!(exception(" ").is_err());
+        assert!(exception(&"a".repeat(MAX_EXCEPTION_LEN + 1)).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use anyhow::{Context as _, Result};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::{
+        command::CommandOptionChoice,
+        component::{button::ButtonStyle, ActionRow, Button, Component},
+        interaction::{
+            ApplicationCommand, ApplicationCommandAutocomplete, MessageComponentInteraction,
+        },
+    },
+    channel::{message::MessageFlags, Attachment},
+    guild::Permissions,
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{marker::GuildMarker, Id},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    database,
+    database::WordKind,
+    filter,
+    interaction::{code, text},
+    Context,
+};
+
+mod transfer;
+
+/// the start of the custom ids of the buttons to change the page of the list
+pub const PAGE_CUSTOM_ID_PREFIX: &str = "words_page:";
+
+/// the maximum number of characters in the words of a page of the list,
+/// leaving room for its title
+const MAX_PAGE_LEN: usize = 1900;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "words", desc = "see or change the words i put in spoilers")]
+pub enum Words {
+    #[command(name = "list")]
+    List(WordsList),
+    #[command(name = "allow")]
+    Allow(WordsAllow),
+    #[command(name = "remove")]
+    Remove(WordsRemove),
+    #[command(name = "unallow")]
+    Unallow(WordsUnallow),
+    #[command(name = "import")]
+    Import(WordsImport),
+    #[command(name = "export")]
+    Export(WordsExport),
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "list", desc = "see the words i put in spoilers")]
+pub struct WordsList;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "allow", desc = "stop putting a word in spoilers")]
+pub struct WordsAllow {
+    #[command(name = "word", desc = "the word to allow", autocomplete = true)]
+    word: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "remove", desc = "remove one of your custom words")]
+pub struct WordsRemove {
+    #[command(name = "word", desc = "the custom word to remove", autocomplete = true)]
+    word: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "unallow",
+    desc = "put a default or shared word you allowed back in spoilers"
+)]
+pub struct WordsUnallow {
+    #[command(name = "word", desc = "the allowed word", autocomplete = true)]
+    word: String,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    name = "import",
+    desc = "add the words in a json, csv or text file, such as one you exported"
+)]
+pub struct WordsImport {
+    #[command(
+        name = "file",
+        desc = "the file with the words, a text file should have a word in each line"
+    )]
+    file: Attachment,
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(name = "export", desc = "get your settings and words as a file")]
+pub struct WordsExport;
+
+/// returns whether the command is `/words import`, which is responded to later
+/// since importing the words can take long
+pub fn is_import(command: &ApplicationCommand) -> bool {
+    command.data.name == "words"
+        && command
+            .data
+            .options
+            .first()
+            .is_some_and(|option| option.name == "import")
+}
+
+pub async fn run(ctx: &Context, command: ApplicationCommand) -> Result<InteractionResponseData> {
+    if !command
+        .member
+        .context("command doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok(text("you need the manage guild permission to use this"));
+    }
+    let guild_id = command
+        .guild_id
+        .context("command doesn't have a guild id")?;
+
+    match Words::from_interaction(command.data.into())? {
+        Words::List(_) => list(ctx, guild_id, 0).await,
+        Words::Allow(options) => {
+            let (word, kind) = WordKind::parse(&options.word);
+            let Some(word_id) = database::words(&ctx.db, guild_id)
+                .await?
+                .into_iter()
+                .find(|w| w.word == word && w.kind == kind)
+                .map(|w| w.id)
+            else {
+                return Ok(text("i don't put this word in spoilers already"));
+            };
+            database::allow_word(&ctx.db, guild_id, word_id).await?;
+            filter::invalidate(ctx, guild_id);
+
+            Ok(text("done!"))
+        }
+        Words::Remove(options) => {
+            let (word, kind) = WordKind::parse(&options.word);
+            if !database::remove_custom_word(&ctx.db, guild_id, &word, kind).await? {
+                return Ok(text("you don't have this custom word"));
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok(text("done!"))
+        }
+        Words::Unallow(options) => {
+            let (word, kind) = WordKind::parse(&options.word);
+            if !database::unallow_word(&ctx.db, guild_id, &word, kind).await? {
+                return Ok(text("this isn't a default word you allowed"));
+            }
+            filter::invalidate(ctx, guild_id);
+
+            Ok(text("done!"))
+        }
+        Words::Import(options) => transfer::import(ctx, guild_id, options.file).await,
+        Words::Export(_) => transfer::export(ctx, guild_id).await,
+    }
+}
+
+/// suggests the words the focused option of the subcommand can take
+pub async fn autocomplete(
+    ctx: &Context,
+    autocomplete: ApplicationCommandAutocomplete,
+) -> Result<Vec<CommandOptionChoice>> {
+    if !autocomplete
+        .member
+        .context("autocomplete interaction doesn't have a member")?
+        .permissions
+        .context("member doesn't have permissions attached")?
+        .contains(Permissions::MANAGE_GUILD)
+    {
+        return Ok(vec![]);
+    }
+    let guild_id = autocomplete
+        .guild_id
+        .context("autocomplete interaction doesn't have a guild id")?;
+
+    let subcommand = autocomplete
+        .data
+        .options
+        .first()
+        .context("autocomplete interaction doesn't have a subcommand")?;
+    let input = subcommand
+        .options
+        .iter()
+        .find(|option| option.focused)
+        .and_then(|option| option.value.as_deref())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let words = match subcommand.name.as_str() {
+        "allow" => database::words(&ctx.db, guild_id).await?,
+        "remove" => database::words(&ctx.db, guild_id)
+            .await?
+            .into_iter()
+            .filter(|word| word.origin.is_none())
+            .collect(),
+        "unallow" => database::allowed_words(&ctx.db, guild_id).await?,
+        _ => vec![],
+    };
+
+    Ok(words
+        .iter()
+        .map(ToString::to_string)
+        .filter(|word| word.contains(&input) && word.chars().count() <= 100)
+        .take(25)
+        .map(|word| CommandOptionChoice::String {
+            name: word.clone(),
+            name_localizations: None,
+            value: word,
+        })
+        .collect())
+}
+
+pub async fn page(
+    ctx: &Context,
+    component: MessageComponentInteraction,
+) -> Result<InteractionResponse> {
+    let guild_id = component
+        .guild_id
+        .context("component interaction doesn't have a guild id")?;
+    let page = component
+        .data
+        .custom_id
+        .strip_prefix(PAGE_CUSTOM_ID_PREFIX)
+        .context("custom id isn't a page custom id")?
+        .parse()?;
+
+    Ok(InteractionResponse {
+        kind: InteractionResponseType::UpdateMessage,
+        data: Some(list(ctx, guild_id, page).await?),
+    })
+}
+
+/// returns the given page of the guild's words with the buttons to change the
+/// page
+#[allow(clippy::integer_arithmetic, clippy::indexing_slicing)]
+async fn list(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    requested_page: usize,
+) -> Result<InteractionResponseData> {
+    let mut words = database::words(&ctx.db, guild_id).await?;
+    if words.is_empty() {
+        return Ok(text("there are no words to put in spoilers"));
+    }
+    words.sort_unstable_by(|word, other| word.word.cmp(&other.word));
+
+    let mut pages = vec![String::new()];
+    for word in words {
+        let line = format!(
+            "- {} ({})\n",
+            code(&word.to_string()),
+            word.origin.as_deref().unwrap_or("custom")
+        );
+        if let Some(last) = pages
+            .last_mut()
+            .filter(|last| last.is_empty() || last.len().saturating_add(line.len()) <= MAX_PAGE_LEN)
+        {
+            last.push_str(&line);
+        } else {
+            pages.push(line);
+        }
+    }
+
+    let page_count = pages.len();
+    let page = requested_page.min(page_count - 1);
+    let content = format!(
+        "**words i put in spoilers** (page {}/{page_count})\n{}",
+        page + 1,
+        pages[page]
+    );
+
+    Ok(InteractionResponseDataBuilder::new()
+        .content(content)
+        .components([Component::ActionRow(ActionRow {
+            components: vec![
+                page_button("previous", page.saturating_sub(1), page == 0),
+                page_button("next", page + 1, page + 1 == page_count),
+            ],
+        })])
+        .flags(MessageFlags::EPHEMERAL)
+        .build())
+}
+
+fn page_button(label: &str, page: usize, disabled: bool) -> Component {
+    Component::Button(Button {
+        custom_id: Some(format!("{PAGE_CUSTOM_ID_PREFIX}{page}")),
+        disabled,
+        emoji: None,
+        label: Some(label.to_owned()),
+        style: ButtonStyle::Secondary,
+        url: None,
+    })
+}
@@ -0,0 +1,578 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    ffi::OsStr,
+    path::Path,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use twilight_model::{
+    channel::{message::MessageFlags, Attachment},
+    http::{attachment::Attachment as FileAttachment, interaction::InteractionResponseData},
+    id::{
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+    bypass,
+    database::{self, Settings, WordKind},
+    download, filter,
+    interaction::{code, text, validate},
+    Context,
+};
+
+/// the maximum size of a file to import in bytes
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// the maximum number of words, exceptions, channels, roles and users in a
+/// file to import
+const MAX_ENTRIES: usize = 1000;
+
+/// the maximum number of rejected words listed in the summary of an import
+const MAX_LISTED_REJECTIONS: usize = 10;
+
+/// the maximum number of characters of a rejected word shown in the summary
+const MAX_SHOWN_LEN: usize = 50;
+
+/// the maximum number of characters in the summary, leaving room for the
+/// number of rejections that aren't listed
+const MAX_SUMMARY_LEN: usize = 1950;
+
+/// the number of words or exceptions added in a query
+const BATCH_LEN: usize = 100;
+
+/// the configuration of a guild, as it's exported
+///
+/// the allowed words, packs, subscriptions, share code and settings aren't
+/// imported
+#[derive(Default, Serialize, Deserialize)]
+struct Config {
+    #[serde(default)]
+    words: Vec<Entry>,
+    #[serde(default)]
+    exceptions: Vec<String>,
+    #[serde(default, skip_deserializing)]
+    allowed_words: Vec<String>,
+    /// the name of each pack and whether it's enabled
+    #[serde(default, skip_deserializing)]
+    packs: BTreeMap<String, bool>,
+    /// the codes of the shared lists the guild subscribed to
+    #[serde(default, skip_deserializing)]
+    subscriptions: Vec<String>,
+    #[serde(default, skip_deserializing)]
+    share_code: Option<String>,
+    #[serde(default, skip_deserializing)]
+    settings: Option<Settings>,
+    /// the settings of the channels and categories that override the guild's
+    #[serde(default)]
+    channels: Vec<ChannelConfig>,
+    #[serde(default)]
+    bypass_roles: Vec<Id<RoleMarker>>,
+    #[serde(default)]
+    opted_out_users: Vec<Id<UserMarker>>,
+}
+
+/// the settings of a channel or category, as they're exported
+#[derive(Serialize, Deserialize)]
+struct ChannelConfig {
+    channel_id: Id<ChannelMarker>,
+    /// whether words are put in spoilers in the channel, `None` if it's the
+    /// same as in its category
+    #[serde(default)]
+    enabled: Option<bool>,
+    /// the names of the only packs used in the channel
+    #[serde(default)]
+    packs: Vec<String>,
+    /// the words added only to the channel, written the way they're added
+    #[serde(default)]
+    words: Vec<String>,
+}
+
+/// a word in a file, written the way it's added with `/add_custom_word`
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Detailed {
+        word: String,
+        #[serde(default)]
+        inflections: bool,
+    },
+    Word(String),
+}
+
+/// the json files that can be imported
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonFile {
+    Config(Box<Config>),
+    Words(Vec<Entry>),
+}
+
+/// what was added or rejected while importing a file
+#[derive(Default)]
+struct Import {
+    /// the number of custom words the guild has, to check the quota
+    word_count: usize,
+    /// the number of regexes the guild has, to check the quota
+    regex_count: usize,
+    added_words: u64,
+    added_exceptions: u64,
+    /// the number of words and exceptions that were already added
+    duplicates: usize,
+    /// the number of channels and categories whose settings were imported
+    channels: usize,
+    added_bypass_roles: usize,
+    added_opted_out_users: usize,
+    rejected: Vec<String>,
+}
+
+impl Import {
+    /// counts a new word of this kind, or returns why it can't be added
+    #[allow(clippy::integer_arithmetic)]
+    fn count(&mut self, kind: WordKind) -> Result<(), String> {
+        validate::quota(self.word_count, self.regex_count, kind)?;
+
+        self.word_count += 1;
+        if kind == WordKind::Regex {
+            self.regex_count += 1;
+        }
+
+        Ok(())
+    }
+
+    /// returns the summary of the import to reply with
+    #[allow(clippy::integer_arithmetic)]
+    fn summary(&self) -> String {
+        let mut summary = format!(
+            "added {} words and {} exceptions, skipped {} that were already added",
+            self.added_words, self.added_exceptions, self.duplicates
+        );
+        if self.channels + self.added_bypass_roles + self.added_opted_out_users > 0 {
+            summary.push_str("\nimported the settings of ");
+            summary.push_str(&self.channels.to_string());
+            summary.push_str(" channels, ");
+            summary.push_str(&self.added_bypass_roles.to_string());
+            summary.push_str(" bypass roles and ");
+            summary.push_str(&self.added_opted_out_users.to_string());
+            summary.push_str(" opted out users");
+        }
+        if !self.rejected.is_empty() {
+            summary.push_str("\nrejected ");
+            summary.push_str(&self.rejected.len().to_string());
+            summary.push(':');
+            let mut listed: usize = 0;
+            for rejection in self.rejected.iter().take(MAX_LISTED_REJECTIONS) {
+                if summary.len() + rejection.len() + 3 > MAX_SUMMARY_LEN {
+                    break;
+                }
+                summary.push_str("\n- ");
+                summary.push_str(rejection);
+                listed += 1;
+            }
+            if self.rejected.len() > listed {
+                summary.push_str("\n- and ");
+                summary.push_str(&(self.rejected.len() - listed).to_string());
+                summary.push_str(" more");
+            }
+        }
+
+        summary
+    }
+}
+
+/// adds the words, exceptions, channel settings, bypass roles and opted out
+/// users in the file to the guild, replying with a summary of what was added
+/// or rejected
+///
+/// the file can be a json file in the format it's exported in or a list of
+/// words, a csv file with the words in its first column and optionally whether
+/// to match their inflections in its second column, or a text file with a word
+/// in each line
+#[allow(clippy::integer_arithmetic)]
+pub async fn import(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    file: Attachment,
+) -> Result<InteractionResponseData> {
+    if file.size > MAX_FILE_SIZE {
+        return Ok(text("the file can't be larger than 1 MiB"));
+    }
+    let Ok(content) = String::from_utf8(download(ctx, &file.url).await?) else {
+        return Ok(text("the file isn't a text file"));
+    };
+    let Some(config) = parse(&file.filename, &content) else {
+        return Ok(text("the file isn't valid json"));
+    };
+    let entry_count = config.words.len()
+        + config.exceptions.len()
+        + config
+            .channels
+            .iter()
+            .map(|channel| 1 + channel.packs.len() + channel.words.len())
+            .sum::<usize>()
+        + config.bypass_roles.len()
+        + config.opted_out_users.len();
+    if entry_count > MAX_ENTRIES {
+        return Ok(text(format!(
+            "the file can't have more than {MAX_ENTRIES} words, exceptions and other settings"
+        )));
+    }
+
+    let mut import = Import {
+        word_count: database::custom_word_count(&ctx.db, guild_id).await?,
+        regex_count: database::regex_word_count(&ctx.db, guild_id).await?,
+        ..Import::default()
+    };
+    import_words(ctx, guild_id, &mut import, config.words, config.exceptions).await?;
+    import_channels(ctx, guild_id, &mut import, config.channels).await?;
+    import_bypasses(
+        ctx,
+        guild_id,
+        &mut import,
+        config.bypass_roles,
+        config.opted_out_users,
+    )
+    .await?;
+
+    filter::invalidate(ctx, guild_id);
+    bypass::invalidate(ctx, guild_id);
+
+    Ok(text(import.summary()))
+}
+
+/// adds the guild's words and exceptions in the file
+#[allow(clippy::integer_arithmetic)]
+async fn import_words(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    import: &mut Import,
+    entries: Vec<Entry>,
+    exceptions: Vec<String>,
+) -> Result<()> {
+    let mut existing = database::words(&ctx.db, guild_id)
+        .await?
+        .into_iter()
+        .filter(|word| word.origin.is_none())
+        .map(|word| word.word)
+        .collect::<HashSet<_>>();
+    let mut new_words = vec![];
+    let mut new_exceptions = vec![];
+
+    for entry in entries {
+        let (raw, inflections) = match entry {
+            Entry::Detailed { word, inflections } => (word, inflections),
+            Entry::Word(word) => (word, false),
+        };
+        let (word, kind) = match validate::word(&raw, false, false) {
+            Ok(parsed) => parsed,
+            Err(reason) => {
+                import.rejected.push(format!(
+                    "{}: {}",
+                    shown(&raw),
+                    reason.lines().next().unwrap_or_default()
+                ));
+                continue;
+            }
+        };
+        if existing.contains(&word) {
+            import.duplicates += 1;
+            continue;
+        }
+        if let Err(reason) = import.count(kind) {
+            import.rejected.push(format!("{}: {reason}", shown(&raw)));
+            continue;
+        }
+
+        existing.insert(word.clone());
+        new_words.push((word, kind, inflections));
+    }
+
+    for raw in exceptions {
+        match validate::exception(&raw) {
+            Ok(exception) => new_exceptions.push(exception),
+            Err(reason) => import.rejected.push(format!("{}: {reason}", shown(&raw))),
+        }
+    }
+
+    for batch in new_words.chunks(BATCH_LEN) {
+        import.added_words += database::add_custom_words(&ctx.db, guild_id, batch).await?;
+    }
+    for batch in new_exceptions.chunks(BATCH_LEN) {
+        import.added_exceptions += database::add_exceptions(&ctx.db, guild_id, batch).await?;
+    }
+    import.duplicates += new_words.len() + new_exceptions.len()
+        - usize::try_from(import.added_words + import.added_exceptions)?;
+
+    Ok(())
+}
+
+/// adds the settings, packs and words of the guild's channels and categories
+/// in the file, skipping the ones that aren't in the guild
+#[allow(clippy::integer_arithmetic)]
+async fn import_channels(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    import: &mut Import,
+    channels: Vec<ChannelConfig>,
+) -> Result<()> {
+    for channel in channels {
+        if ctx
+            .cache
+            .channel(channel.channel_id)
+            .map(|cached| cached.guild_id)
+            != Some(Some(guild_id))
+        {
+            import.rejected.push(format!(
+                "channel {}: it isn't in this server",
+                code(&channel.channel_id.to_string())
+            ));
+            continue;
+        }
+
+        if let Some(enabled) = channel.enabled {
+            database::set_channel_enabled(&ctx.db, guild_id, channel.channel_id, enabled).await?;
+        }
+        for pack in channel.packs {
+            database::add_channel_pack(&ctx.db, guild_id, channel.channel_id, &pack).await?;
+        }
+
+        let mut existing = database::channel_words(&ctx.db, channel.channel_id)
+            .await?
+            .into_iter()
+            .map(|word| word.word)
+            .collect::<HashSet<_>>();
+        for raw in channel.words {
+            let (word, kind) = match validate::word(&raw, false, false) {
+                Ok(parsed) => parsed,
+                Err(reason) => {
+                    import.rejected.push(format!(
+                        "{}: {}",
+                        shown(&raw),
+                        reason.lines().next().unwrap_or_default()
+                    ));
+                    continue;
+                }
+            };
+            if existing.contains(&word) {
+                continue;
+            }
+            if let Err(reason) = import.count(kind) {
+                import.rejected.push(format!("{}: {reason}", shown(&raw)));
+                continue;
+            }
+
+            existing.insert(word.clone());
+            database::add_channel_word(&ctx.db, guild_id, channel.channel_id, word, kind).await?;
+        }
+
+        import.channels += 1;
+    }
+
+    Ok(())
+}
+
+/// adds the guild's bypass roles and opted out users in the file, skipping the
+/// roles that aren't in the guild
+#[allow(clippy::integer_arithmetic)]
+async fn import_bypasses(
+    ctx: &Context,
+    guild_id: Id<GuildMarker>,
+    import: &mut Import,
+    role_ids: Vec<Id<RoleMarker>>,
+    user_ids: Vec<Id<UserMarker>>,
+) -> Result<()> {
+    for role_id in role_ids {
+        if ctx.cache.role(role_id).map(|role| role.guild_id()) != Some(guild_id) {
+            import.rejected.push(format!(
+                "role {}: it isn't in this server",
+                code(&role_id.to_string())
+            ));
+            continue;
+        }
+
+        if database::add_bypass_role(&ctx.db, guild_id, role_id).await? {
+            import.added_bypass_roles += 1;
+        }
+    }
+
+    for user_id in user_ids {
+        if database::set_opted_out(&ctx.db, guild_id, user_id, true).await? {
+            import.added_opted_out_users += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// returns the start of the rejected word or exception as code to show it in
+/// the summary
+fn shown(raw: &str) -> String {
+    let mut shortened = raw
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_SHOWN_LEN)
+        .collect::<String>();
+    if raw.chars().count() > MAX_SHOWN_LEN {
+        shortened.push_str("...");
+    }
+
+    code(&shortened)
+}
+
+/// replies with a json file of the guild's configuration
+pub async fn export(ctx: &Context, guild_id: Id<GuildMarker>) -> Result<InteractionResponseData> {
+    let mut channels = vec![];
+    for settings in database::guild_channel_settings(&ctx.db, guild_id).await? {
+        channels.push(ChannelConfig {
+            channel_id: settings.channel_id,
+            enabled: settings.enabled,
+            packs: database::channel_packs(&ctx.db, settings.channel_id).await?,
+            words: database::channel_words(&ctx.db, settings.channel_id)
+                .await?
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        });
+    }
+
+    let config = Config {
+        words: database::words(&ctx.db, guild_id)
+            .await?
+            .into_iter()
+            .filter(|word| word.origin.is_none())
+            .map(|word| Entry::Detailed {
+                word: word.to_string(),
+                inflections: word.inflections,
+            })
+            .collect(),
+        exceptions: database::exceptions(&ctx.db, guild_id).await?,
+        allowed_words: database::allowed_words(&ctx.db, guild_id)
+            .await?
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        packs: database::packs(&ctx.db, guild_id)
+            .await?
+            .into_iter()
+            .map(|pack| (pack.name, pack.enabled))
+            .collect(),
+        subscriptions: database::subscriptions(&ctx.db, guild_id)
+            .await?
+            .into_iter()
+            .map(|subscription| subscription.code)
+            .collect(),
+        share_code: database::share_code(&ctx.db, guild_id).await?,
+        settings: Some(database::settings(&ctx.db, guild_id).await?),
+        channels,
+        bypass_roles: database::bypass_roles(&ctx.db, guild_id).await?,
+        opted_out_users: database::opted_out_users(&ctx.db, guild_id).await?,
+    };
+
+    Ok(InteractionResponseDataBuilder::new()
+        .content(
+            "here are your settings and words, you can add the words, exceptions, channel \
+             settings, bypass roles and opt outs to another server with `/words import`"
+                .to_owned(),
+        )
+        .attachments([FileAttachment::from_bytes(
+            "words.json".to_owned(),
+            serde_json::to_vec_pretty(&config)?,
+            0,
+        )])
+        .flags(MessageFlags::EPHEMERAL)
+        .build())
+}
+
+/// returns the configuration in the file, only with words if it isn't a json
+/// file, or `None` if it's an invalid json file
+fn parse(filename: &str, content: &str) -> Option<Config> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(OsStr::to_str)
+        .unwrap_or_default()
+        .to_lowercase();
+
+    if extension == "json" {
+        return Some(match serde_json::from_str(content).ok()? {
+            JsonFile::Config(config) => *config,
+            JsonFile::Words(words) => Config {
+                words,
+                ..Config::default()
+            },
+        });
+    }
+
+    let lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let entries = if extension == "csv" {
+        lines
+            .filter_map(|line| {
+                let mut cells = line.split(',').map(|cell| cell.trim().trim_matches('"'));
+                let word = cells.next()?;
+                let inflections = cells
+                    .next()
+                    .is_some_and(|cell| matches!(cell, "true" | "yes" | "1"));
+                (word != "word").then(|| Entry::Detailed {
+                    word: word.to_owned(),
+                    inflections,
+                })
+            })
+            .collect()
+    } else {
+        lines.map(|line| Entry::Word(line.to_owned())).collect()
+    };
+
+    Some(Config {
+        words: entries,
+        ..Config::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::id::Id;
+
+    use super::parse;
+
+    #[test]
+    fn exported_settings_are_parsed() {
+        let config = parse(
+            "words.json",
+            r#"{
+                "words": [{ "word": "kill", "inflections": true }],
+                "channels": [
+                    { "channel_id": "1", "enabled": false, "packs": ["slurs"], "words": ["die*"] }
+                ],
+                "bypass_roles": ["2"],
+                "opted_out_users": ["3"]
+            }"#,
+        );
+
+        assert!(config.as_ref().is_some_and(|parsed| parsed.words.len() == 1
+            && parsed.channels.len() == 1
+            && parsed
+                .channels
+                .iter()
+                .all(|channel| channel.channel_id == Id::new(1)
+                    && channel.enabled == Some(false)
+                    && channel.packs == ["slurs"]
+                    && channel.words == ["die*"])
+            && parsed.bypass_roles == [Id::new(2)]
+            && parsed.opted_out_users == [Id::new(3)]));
+    }
+
+    #[test]
+    fn text_files_only_have_words() {
+        let config = parse("words.txt", "kill\n# a comment\n\ndie*\n");
+
+        assert!(config.as_ref().is_some_and(|parsed| parsed.words.len() == 2
+            && parsed.exceptions.is_empty()
+            && parsed.channels.is_empty()));
+    }
+}
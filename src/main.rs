@@ -7,10 +7,16 @@
     clippy::pattern_type_mismatch
 )]
 
-use std::{env, sync::Arc};
+use std::{
+    env,
+    sync::{atomic::AtomicU64, Arc},
+};
 
-use anyhow::{Context as _, Result};
+use anyhow::{bail, Context as _, Result};
+use dashmap::{DashMap, DashSet};
 use futures_util::StreamExt;
+use hyper::client::{Client as HyperClient, HttpConnector};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
 use sqlx::PgPool;
 use twilight_cache_inmemory::{InMemoryCache, ResourceType};
 use twilight_gateway::{Cluster, EventTypeFlags};
@@ -20,8 +26,9 @@ use twilight_model::{
     channel::{message::AllowedMentions, Channel},
     gateway::{event::Event, Intents},
     guild::{PartialMember, Permissions},
+    http::attachment::Attachment,
     id::{
-        marker::{ApplicationMarker, ChannelMarker, GuildMarker, UserMarker},
+        marker::{ApplicationMarker, ChannelMarker, GuildMarker, MessageMarker, UserMarker},
         Id,
     },
     user::User,
@@ -32,19 +39,37 @@ use twilight_webhook::{
 };
 
 mod auto_spoiler;
+mod bypass;
 mod database;
+mod filter;
 mod interaction;
 
 pub type Context = Arc<ContextInner>;
 
 pub struct ContextInner {
     http: Client,
+    attachment_client: HyperClient<HttpsConnector<HttpConnector>>,
     cache: InMemoryCache,
     db: PgPool,
     webhooks: WebhooksCache,
+    filters: DashMap<Id<GuildMarker>, Arc<filter::Filter>>,
+    channel_filters: DashMap<Id<ChannelMarker>, filter::ChannelFilter>,
+    /// incremented when the cached filters are invalidated, see
+    /// [`filter::invalidate`]
+    filters_generation: AtomicU64,
+    /// the messages whose words are being put in spoilers, so that an update of
+    /// a message isn't handled at the same time
+    editing: DashSet<Id<MessageMarker>>,
+    bypasses: DashMap<Id<GuildMarker>, Arc<bypass::Bypass>>,
+    /// incremented when the cached bypasses are invalidated, see
+    /// [`bypass::invalidate`]
+    bypasses_generation: AtomicU64,
     application_id: Id<ApplicationMarker>,
     user_id: Id<UserMarker>,
     owner_channel_id: Id<ChannelMarker>,
+    /// the users that can use the owner commands, including the application
+    /// owner
+    owner_ids: Vec<Id<UserMarker>>,
 }
 
 #[tokio::main]
@@ -58,6 +83,7 @@ async fn main() -> Result<()> {
     let event_types = EventTypeFlags::INTERACTION_CREATE
         | EventTypeFlags::WEBHOOKS_UPDATE
         | EventTypeFlags::MESSAGE_CREATE
+        | EventTypeFlags::MESSAGE_UPDATE
         | EventTypeFlags::GUILD_CREATE
         | EventTypeFlags::GUILD_UPDATE
         | EventTypeFlags::GUILD_DELETE
@@ -100,30 +126,46 @@ async fn main() -> Result<()> {
         .model()
         .await?;
 
+    let application_owner_id = application
+        .owner
+        .context("application doesn't include owner")?
+        .id;
     let owner_channel_id = http
-        .create_private_channel(
-            application
-                .owner
-                .context("application doesn't include owner")?
-                .id,
-        )
+        .create_private_channel(application_owner_id)
         .exec()
         .await?
         .model()
         .await?
         .id;
 
-    interaction::create(&http, application.id).await?;
+    let mut owner_ids = env_ids("OWNER_USER_IDS")?;
+    owner_ids.push(application_owner_id);
+
+    interaction::create(&http, application.id, &env_ids("OWNER_GUILD_IDS")?).await?;
 
     let ctx = Arc::new(ContextInner {
+        attachment_client: HyperClient::builder().build(
+            HttpsConnectorBuilder::new()
+                .with_native_roots()
+                .https_only()
+                .enable_http1()
+                .build(),
+        ),
         cache: InMemoryCache::builder()
             .resource_types(resource_types)
             .build(),
         db: PgPool::connect(&env::var("DATABASE_URL")?).await?,
         webhooks: WebhooksCache::new(),
+        filters: DashMap::new(),
+        channel_filters: DashMap::new(),
+        filters_generation: AtomicU64::new(0),
+        editing: DashSet::new(),
+        bypasses: DashMap::new(),
+        bypasses_generation: AtomicU64::new(0),
         user_id: http.current_user().exec().await?.model().await?.id,
         application_id: application.id,
         owner_channel_id,
+        owner_ids,
         http,
     });
 
@@ -160,6 +202,18 @@ async fn _handle_event(ctx: Context, event: Event) -> Result<()> {
                 .await?;
         }
         Event::MessageCreate(message) => auto_spoiler::edit(ctx, (*message).0).await?,
+        Event::MessageUpdate(update) => auto_spoiler::edit_update(ctx, *update).await?,
+        Event::GuildDelete(guild) => {
+            filter::invalidate(&ctx, guild.id);
+            bypass::invalidate(&ctx, guild.id);
+        }
+        Event::ChannelUpdate(channel) => filter::invalidate_channel(&ctx, channel.id),
+        Event::ChannelDelete(channel) => {
+            filter::invalidate_channel(&ctx, channel.id);
+            if let Some(guild_id) = channel.guild_id {
+                database::reset_channel(&ctx.db, guild_id, channel.id).await?;
+            }
+        }
         _ => (),
     }
     Ok(())
@@ -175,6 +229,7 @@ async fn webhook(
     thread_id: Option<Id<ChannelMarker>>,
     content: &str,
     components: &[Component],
+    attachments: &[Attachment],
 ) -> Result<()> {
     MinimalWebhook::try_from(
         &*ctx
@@ -189,12 +244,40 @@ async fn webhook(
     )?
     .content(content)?
     .components(components)?
+    .attachments(attachments)?
     .exec()
     .await?;
 
     Ok(())
 }
 
+async fn download(ctx: &Context, url: &str) -> Result<Vec<u8>> {
+    let response = ctx.attachment_client.get(url.parse()?).await?;
+    if !response.status().is_success() {
+        bail!("downloading {url} failed: {}", response.status());
+    }
+
+    Ok(hyper::body::to_bytes(response.into_body()).await?.to_vec())
+}
+
+/// parses the comma separated ids in the environment variable, returning no
+/// ids if it isn't set
+fn env_ids<T>(key: &str) -> Result<Vec<Id<T>>> {
+    let Ok(value) = env::var(key) else {
+        return Ok(vec![]);
+    };
+
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse()
+                .with_context(|| format!("`{id}` in {key} isn't a valid id"))
+        })
+        .collect()
+}
+
 fn has_permissions(
     ctx: &Context,
     channel_id: Id<ChannelMarker>,